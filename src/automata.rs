@@ -0,0 +1,464 @@
+//! Cellular automata over a [BitGrid], for cave generation and other
+//! life-like simulations.
+//!
+//! Neighbors are counted a whole word of cells at a time using the grid's
+//! underlying bit storage rather than reading each cell individually.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{automata::{Automaton, Boundary, Rule}, BitGrid};
+//!
+//! let mut grid = BitGrid::new([5, 5]);
+//! grid.set_true([1, 2]);
+//! grid.set_true([2, 2]);
+//! grid.set_true([3, 2]);
+//!
+//! let rule: Rule = "B3/S23".parse().unwrap();
+//! let mut life = Automaton::new(grid, rule, Boundary::Empty);
+//! life.step();
+//!
+//! assert!(life.grid().get([2, 1]));
+//! assert!(life.grid().get([2, 3]));
+//! assert!(!life.grid().get([1, 2]));
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    bit_grid::{low_bits, read_block_bits, write_block_bits},
+    BitGrid, SizedGrid,
+};
+
+/// A life-like birth and survival rule.
+///
+/// Determines the next state of a cell from it's current state and the number
+/// of it's eight neighbors that are alive.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    /// Create a rule from the neighbor counts where a dead cell becomes alive
+    /// and where a live cell stays alive.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mut rule = Self {
+            birth: [false; 9],
+            survival: [false; 9],
+        };
+        for &n in birth.iter().chain(survival) {
+            assert!(
+                n <= 8,
+                "Attempting to create a rule with an invalid neighbor count {}",
+                n
+            );
+        }
+        birth.iter().for_each(|&n| rule.birth[n as usize] = true);
+        survival
+            .iter()
+            .for_each(|&n| rule.survival[n as usize] = true);
+        rule
+    }
+
+    /// Create a rule from a function taking the current state of a cell and
+    /// it's number of live neighbors, returning the cell's next state.
+    pub fn from_fn(f: impl Fn(bool, u8) -> bool) -> Self {
+        Self {
+            birth: std::array::from_fn(|n| f(false, n as u8)),
+            survival: std::array::from_fn(|n| f(true, n as u8)),
+        }
+    }
+
+    /// The next state of a cell with the given number of live neighbors.
+    #[inline]
+    pub fn next_state(&self, alive: bool, neighbors: u8) -> bool {
+        match alive {
+            true => self.survival[neighbors as usize],
+            false => self.birth[neighbors as usize],
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parse a rule in birth/survival notation, ie: `"B3/S23"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut birth = None;
+        let mut survival = None;
+        for part in s.trim().split('/') {
+            let mut chars = part.chars();
+            let counts = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if birth.is_none() => birth.insert(Vec::new()),
+                Some('S') if survival.is_none() => survival.insert(Vec::new()),
+                _ => return Err(ParseRuleError::InvalidFormat),
+            };
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => counts.push(n as u8),
+                    _ => return Err(ParseRuleError::InvalidCount(c)),
+                }
+            }
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rule::new(&birth, &survival)),
+            _ => Err(ParseRuleError::InvalidFormat),
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |set: &[bool; 9]| -> String {
+            (0..9)
+                .filter(|n| set[*n])
+                .map(|n| char::from_digit(n as u32, 10).unwrap())
+                .collect()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+/// An error returned when parsing a [Rule] from a string.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParseRuleError {
+    /// The rule isn't in the form `B{counts}/S{counts}`.
+    InvalidFormat,
+    /// The rule contains a character that isn't a neighbor count from 0 to 8.
+    InvalidCount(char),
+}
+
+impl Display for ParseRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseRuleError::InvalidFormat => {
+                write!(f, "Rule should be in the form B{{counts}}/S{{counts}}")
+            }
+            ParseRuleError::InvalidCount(c) => write!(f, "Invalid neighbor count '{}'", c),
+        }
+    }
+}
+
+impl std::error::Error for ParseRuleError {}
+
+/// How cells outside the grid are treated when counting neighbors.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary {
+    /// Cells outside the grid are alive.
+    Wall,
+    /// The grid wraps around at it's edges.
+    Wrap,
+    /// Cells outside the grid are dead.
+    #[default]
+    Empty,
+}
+
+/// Steps a [BitGrid] through a [Rule], using a second grid as a buffer for
+/// the next state.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "AutomatonData"))]
+pub struct Automaton {
+    grid: BitGrid,
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    back: BitGrid,
+    rule: Rule,
+    boundary: Boundary,
+}
+
+/// Serialized automaton state. The back buffer isn't saved, it's recreated to
+/// match the size of the grid when an [Automaton] is deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AutomatonData {
+    grid: BitGrid,
+    rule: Rule,
+    boundary: Boundary,
+}
+
+#[cfg(feature = "serde")]
+impl From<AutomatonData> for Automaton {
+    fn from(data: AutomatonData) -> Self {
+        Self::new(data.grid, data.rule, data.boundary)
+    }
+}
+
+impl Automaton {
+    /// Create an automaton starting from the given grid. The grid is moved in
+    /// as the current state, and a new empty grid of the same size is
+    /// allocated as the back buffer.
+    pub fn new(grid: BitGrid, rule: Rule, boundary: Boundary) -> Self {
+        let back = BitGrid::new(grid.size());
+        Self {
+            grid,
+            back,
+            rule,
+            boundary,
+        }
+    }
+
+    /// The current state of the automaton.
+    pub fn grid(&self) -> &BitGrid {
+        &self.grid
+    }
+
+    /// The current state of the automaton.
+    pub fn grid_mut(&mut self) -> &mut BitGrid {
+        &mut self.grid
+    }
+
+    /// Consume the automaton and return it's current state.
+    pub fn into_grid(self) -> BitGrid {
+        self.grid
+    }
+
+    /// The rule applied on each step.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Change the rule applied on each step.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// How cells outside the grid are treated.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Change how cells outside the grid are treated.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Advance the automaton by a single step.
+    pub fn step(&mut self) {
+        step_into(&self.grid, &mut self.back, &self.rule, self.boundary);
+        std::mem::swap(&mut self.grid, &mut self.back);
+    }
+
+    /// Advance the automaton by the given number of steps.
+    pub fn steps(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
+
+/// Advance a grid through a rule by the given number of steps.
+pub fn step(grid: &mut BitGrid, rule: &Rule, boundary: Boundary, steps: usize) {
+    let mut back = BitGrid::new(grid.size());
+    for _ in 0..steps {
+        step_into(grid, &mut back, rule, boundary);
+        std::mem::swap(grid, &mut back);
+    }
+}
+
+/// Write the next state of `src` into `dst`.
+fn step_into(src: &BitGrid, dst: &mut BitGrid, rule: &Rule, boundary: Boundary) {
+    assert_eq!(
+        src.size(),
+        dst.size(),
+        "Attempting to step an automaton into a grid of a different size"
+    );
+    let [w, h] = src.size().to_array().map(|v| v as usize);
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    // Each row is copied into words with an extra cell on either side, and an
+    // extra row above and below, filled according to the boundary.
+    let row_words = (w + 2).div_ceil(64);
+    let mut padded = vec![0u64; row_words * (h + 2)];
+    let blocks = src.bits().storage();
+    for y in 0..h {
+        let row = &mut padded[(y + 1) * row_words..(y + 2) * row_words];
+        for x in (0..w).step_by(64) {
+            let len = (w - x).min(64);
+            write_bits(row, x + 1, len, read_block_bits(blocks, y * w + x, len));
+        }
+        let (left, right) = match boundary {
+            Boundary::Wall => (1, 1),
+            Boundary::Wrap => (
+                src.get_index(y * w + w - 1) as u64,
+                src.get_index(y * w) as u64,
+            ),
+            Boundary::Empty => (0, 0),
+        };
+        write_bits(row, 0, 1, left);
+        write_bits(row, w + 1, 1, right);
+    }
+    match boundary {
+        Boundary::Wall => {
+            let full = padded.len() - row_words;
+            padded[..row_words].fill(u64::MAX);
+            padded[full..].fill(u64::MAX);
+        }
+        Boundary::Wrap => {
+            padded.copy_within(h * row_words..(h + 1) * row_words, 0);
+            padded.copy_within(row_words..2 * row_words, (h + 1) * row_words);
+        }
+        Boundary::Empty => {}
+    }
+
+    let birth = rule.birth;
+    let survival = rule.survival;
+    dst.with_blocks_mut(|out| {
+        for y in 0..h {
+            let rows = [y, y + 1, y + 2].map(|r| &padded[r * row_words..(r + 1) * row_words]);
+            for x in (0..w).step_by(64) {
+                let k = x / 64;
+                // Columns left, center and right of each cell in a row.
+                let cols = rows.map(|row| {
+                    let next = row.get(k + 1).copied().unwrap_or(0);
+                    [row[k], row[k] >> 1 | next << 63, row[k] >> 2 | next << 62]
+                });
+                let center = cols[1][1];
+                let neighbors = [
+                    cols[0][0], cols[0][1], cols[0][2], cols[1][0], cols[1][2], cols[2][0],
+                    cols[2][1], cols[2][2],
+                ];
+
+                // Count neighbors for every cell at once, with each bit of the
+                // count stored in a separate plane.
+                let mut planes = [0u64; 4];
+                for n in neighbors {
+                    let mut carry = n;
+                    for plane in planes.iter_mut() {
+                        let next_carry = *plane & carry;
+                        *plane ^= carry;
+                        carry = next_carry;
+                    }
+                }
+
+                let mut next = 0;
+                for count in 0..9 {
+                    if !birth[count] && !survival[count] {
+                        continue;
+                    }
+                    let is_count = planes.iter().enumerate().fold(u64::MAX, |acc, (i, p)| {
+                        acc & if count >> i & 1 == 1 { *p } else { !*p }
+                    });
+                    if birth[count] {
+                        next |= is_count & !center;
+                    }
+                    if survival[count] {
+                        next |= is_count & center;
+                    }
+                }
+                let len = (w - x).min(64);
+                write_block_bits(out, y * w + x, len, next);
+            }
+        }
+    });
+}
+
+/// Write up to 64 bits into a list of words starting at the given bit index.
+fn write_bits(words: &mut [u64], start: usize, len: usize, value: u64) {
+    let mut written = 0;
+    while written < len {
+        let i = start + written;
+        let n = (64 - i % 64).min(len - written);
+        let m = low_bits(n) << (i % 64);
+        let bits = (value >> written) << (i % 64);
+        words[i / 64] = (words[i / 64] & !m) | (bits & m);
+        written += n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bit_grid::noise, GridPoint};
+
+    /// Step a grid one cell at a time.
+    fn naive_step(grid: &BitGrid, rule: &Rule, boundary: Boundary) -> BitGrid {
+        let size = grid.size().as_ivec2();
+        let mut next = BitGrid::new(grid.size());
+        for (p, alive) in grid.iter_xy() {
+            let count = p
+                .adj_8()
+                .filter(|n| match boundary {
+                    _ if grid.in_bounds(*n) => grid.get(*n),
+                    Boundary::Wall => true,
+                    Boundary::Wrap => grid.get(n.rem_euclid(size)),
+                    Boundary::Empty => false,
+                })
+                .count();
+            next.set(p, rule.next_state(alive, count as u8));
+        }
+        next
+    }
+
+    #[test]
+    fn parse() {
+        let rule: Rule = "B3/S23".parse().unwrap();
+        assert_eq!(Rule::new(&[3], &[2, 3]), rule);
+        assert_eq!("B3/S23", rule.to_string());
+        assert_eq!(rule, "s23/b3".parse().unwrap());
+        assert_eq!(Ok(Rule::new(&[], &[])), "B/S".parse());
+        assert_eq!(
+            Err(ParseRuleError::InvalidCount('9')),
+            "B9/S1".parse::<Rule>()
+        );
+        assert_eq!(Err(ParseRuleError::InvalidFormat), "B3".parse::<Rule>());
+
+        let from_fn = Rule::from_fn(|alive, n| n == 3 || (alive && n == 2));
+        assert_eq!(rule, from_fn);
+    }
+
+    #[test]
+    fn matches_naive() {
+        let rules = ["B3/S23", "B5678/S45678", "B0/S8", "B012345678/S"];
+        for size in [[1, 1], [3, 7], [64, 3], [70, 20], [130, 5]] {
+            for rule in rules {
+                let rule: Rule = rule.parse().unwrap();
+                for boundary in [Boundary::Wall, Boundary::Wrap, Boundary::Empty] {
+                    let mut grid = noise(size, size[0], 2);
+                    for _ in 0..3 {
+                        let expected = naive_step(&grid, &rule, boundary);
+                        step(&mut grid, &rule, boundary, 1);
+                        assert_eq!(expected.bits(), grid.bits(), "{size:?} {rule} {boundary:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blinker() {
+        let mut grid = BitGrid::new([5, 5]);
+        for x in 1..4 {
+            grid.set_true([x, 2]);
+        }
+        let start = grid.clone();
+        let mut life = Automaton::new(grid, "B3/S23".parse().unwrap(), Boundary::Wrap);
+        life.steps(2);
+        assert_eq!(start.bits(), life.grid().bits());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = BitGrid::new([5, 5]);
+        for x in 1..4 {
+            grid.set_true([x, 2]);
+        }
+        let life = Automaton::new(grid, "B3/S23".parse().unwrap(), Boundary::Wrap);
+        let json = serde_json::to_string(&life).unwrap();
+        assert!(!json.contains("back"));
+
+        let mut loaded: Automaton = serde_json::from_str(&json).unwrap();
+        assert_eq!(life.rule(), loaded.rule());
+        assert_eq!(life.boundary(), loaded.boundary());
+        assert_eq!(life.grid().bits(), loaded.grid().bits());
+        loaded.step();
+        assert!(loaded.grid().get([2, 1]));
+        assert!(!loaded.grid().get([1, 2]));
+    }
+}
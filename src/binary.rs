@@ -0,0 +1,537 @@
+//! A compact binary format for saving and loading grids.
+//!
+//! Every file starts with a small header, with all values stored as little
+//! endian:
+//!
+//! | Bytes | Value                                             |
+//! |-------|---------------------------------------------------|
+//! | 4     | Magic value, `b"SGRD"`                            |
+//! | 1     | Format version                                    |
+//! | 1     | Grid kind (`0`: [Grid], `1`: [BitGrid], `2`: [FloatGrid]) |
+//! | 1     | [Compression]                                     |
+//! | 4     | Grid width                                        |
+//! | 4     | Grid height                                       |
+//!
+//! The header is followed by the grid data. Uncompressed data is stored as
+//! each value in order, with [BitGrid] bits packed into bytes. Run length
+//! encoded data is stored as a sequence of runs, each a `u32` count followed
+//! by the value.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{binary::Compression, Grid};
+//!
+//! let mut grid = Grid::<u16>::new([32, 32]);
+//! grid[[5, 5]] = 10;
+//!
+//! let bytes = grid.to_bytes(Compression::RunLength);
+//! assert!(bytes.len() < 64);
+//!
+//! let loaded = Grid::<u16>::from_bytes(&bytes).unwrap();
+//! assert_eq!(grid, loaded);
+//! ```
+
+use glam::UVec2;
+
+use crate::{BitGrid, FloatGrid, Grid, SizedGrid};
+
+/// The magic value every file starts with.
+pub const MAGIC: [u8; 4] = *b"SGRD";
+
+/// The current version of the format.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 15;
+
+/// How grid data is compressed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Compression {
+    /// Every value is stored in order.
+    #[default]
+    None,
+    /// Repeated values are stored as a single run, which works well for
+    /// mostly uniform grids.
+    RunLength,
+}
+
+/// An error that can occur when loading a grid from bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data ended before the grid was fully read.
+    Truncated,
+    /// The data doesn't start with [MAGIC].
+    InvalidMagic,
+    /// The data was written with an unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// The data contains a different kind of grid than the one being loaded.
+    WrongKind { expected: u8, found: u8 },
+    /// The data uses an unknown compression method.
+    UnknownCompression(u8),
+    /// The amount of grid data doesn't match the size in the header.
+    SizeMismatch { expected: usize, found: usize },
+    /// A grid value couldn't be decoded.
+    InvalidValue,
+    /// The grid size or run counts are too large to be addressed on this
+    /// platform.
+    TooLarge,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "Grid data is truncated"),
+            DecodeError::InvalidMagic => write!(f, "Grid data has an invalid magic value"),
+            DecodeError::UnsupportedVersion(v) => {
+                write!(f, "Grid data has an unsupported version {}", v)
+            }
+            DecodeError::WrongKind { expected, found } => {
+                write!(f, "Expected grid kind {}, found {}", expected, found)
+            }
+            DecodeError::UnknownCompression(c) => {
+                write!(f, "Grid data has an unknown compression method {}", c)
+            }
+            DecodeError::SizeMismatch { expected, found } => write!(
+                f,
+                "Grid data length {} doesn't match the expected length {}",
+                found, expected
+            ),
+            DecodeError::InvalidValue => write!(f, "Grid data contains an invalid value"),
+            DecodeError::TooLarge => write!(f, "Grid data is too large to load"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// A value that can be stored in the binary grid format. Every value of a
+/// type is encoded to the same number of bytes.
+pub trait GridEncode: Sized {
+    /// The number of bytes a value is encoded to. Must be greater than zero,
+    /// loading a grid will panic otherwise.
+    const SIZE: usize;
+
+    /// Append the encoded value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from exactly [GridEncode::SIZE] bytes, returning [None]
+    /// if the bytes don't represent a valid value.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_grid_encode {
+    ($type:ty) => {
+        impl GridEncode for $type {
+            const SIZE: usize = std::mem::size_of::<$type>();
+
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(bytes: &[u8]) -> Option<Self> {
+                Some(<$type>::from_le_bytes(bytes.try_into().ok()?))
+            }
+        }
+    };
+}
+
+impl_grid_encode!(u8);
+impl_grid_encode!(u16);
+impl_grid_encode!(u32);
+impl_grid_encode!(u64);
+impl_grid_encode!(i8);
+impl_grid_encode!(i16);
+impl_grid_encode!(i32);
+impl_grid_encode!(i64);
+impl_grid_encode!(f32);
+impl_grid_encode!(f64);
+
+impl GridEncode for bool {
+    const SIZE: usize = 1;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl GridEncode for char {
+    const SIZE: usize = 4;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        char::from_u32(u32::decode(bytes)?)
+    }
+}
+
+const KIND_GRID: u8 = 0;
+const KIND_BIT_GRID: u8 = 1;
+const KIND_FLOAT_GRID: u8 = 2;
+
+impl<T: GridEncode> Grid<T> {
+    /// Encode the grid in the binary grid format.
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let mut out = write_header(KIND_GRID, compression, self.size());
+        write_values(self.slice(), compression, &mut out);
+        out
+    }
+
+    /// Load a grid from bytes written by [Grid::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (compression, size, data) = read_header(bytes, KIND_GRID)?;
+        let values = read_values(data, compression, tile_count(size)?)?;
+        Ok(Grid::from_parts(values, size))
+    }
+}
+
+impl FloatGrid {
+    /// Encode the grid in the binary grid format.
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let mut out = write_header(KIND_FLOAT_GRID, compression, self.size());
+        write_values(self.values(), compression, &mut out);
+        out
+    }
+
+    /// Load a grid from bytes written by [FloatGrid::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (compression, size, data) = read_header(bytes, KIND_FLOAT_GRID)?;
+        let values = read_values::<f32>(data, compression, tile_count(size)?)?;
+        let mut grid = FloatGrid::new(size);
+        grid.values_mut().copy_from_slice(&values);
+        Ok(grid)
+    }
+}
+
+impl BitGrid {
+    /// Encode the grid in the binary grid format.
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let mut out = write_header(KIND_BIT_GRID, compression, self.size());
+        match compression {
+            Compression::None => out.extend(self.bits().to_bytes()),
+            Compression::RunLength => {
+                let mut iter = self.iter().peekable();
+                while let Some(bit) = iter.next() {
+                    let mut count: u32 = 1;
+                    while count < u32::MAX && iter.next_if_eq(&bit).is_some() {
+                        count += 1;
+                    }
+                    count.encode(&mut out);
+                    bit.encode(&mut out);
+                }
+            }
+        }
+        out
+    }
+
+    /// Load a grid from bytes written by [BitGrid::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (compression, size, data) = read_header(bytes, KIND_BIT_GRID)?;
+        let len = tile_count(size)?;
+        // The grid is only allocated once the data is known to match the size
+        // in the header.
+        match compression {
+            Compression::None => {
+                check_len(data.len(), len.div_ceil(8))?;
+                let mut bits = bit_vec::BitVec::from_bytes(data);
+                bits.truncate(len);
+                let mut grid = BitGrid::new(size);
+                *grid.bits_mut() = bits;
+                Ok(grid)
+            }
+            Compression::RunLength => {
+                let runs = read_runs(data, bool::SIZE)?;
+                check_run_len(&runs, len)?;
+                let mut grid = BitGrid::new(size);
+                let mut i = 0;
+                for (count, bytes) in runs {
+                    if bool::decode(bytes).ok_or(DecodeError::InvalidValue)? {
+                        (i..i + count).for_each(|i| grid.set_index_true(i));
+                    }
+                    i += count;
+                }
+                Ok(grid)
+            }
+        }
+    }
+}
+
+fn write_header(kind: u8, compression: Compression, size: UVec2) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN);
+    out.extend_from_slice(&MAGIC);
+    out.push(VERSION);
+    out.push(kind);
+    out.push(compression as u8);
+    size.x.encode(&mut out);
+    size.y.encode(&mut out);
+    out
+}
+
+/// Validate the header and return the compression, grid size and the
+/// remaining grid data.
+fn read_header(bytes: &[u8], kind: u8) -> Result<(Compression, UVec2, &[u8]), DecodeError> {
+    if bytes.len() < MAGIC.len() {
+        return Err(DecodeError::Truncated);
+    }
+    if bytes[..MAGIC.len()] != MAGIC {
+        return Err(DecodeError::InvalidMagic);
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(DecodeError::Truncated);
+    }
+    if bytes[4] != VERSION {
+        return Err(DecodeError::UnsupportedVersion(bytes[4]));
+    }
+    if bytes[5] != kind {
+        return Err(DecodeError::WrongKind {
+            expected: kind,
+            found: bytes[5],
+        });
+    }
+    let compression = match bytes[6] {
+        0 => Compression::None,
+        1 => Compression::RunLength,
+        c => return Err(DecodeError::UnknownCompression(c)),
+    };
+    let width = u32::decode(&bytes[7..11]).unwrap();
+    let height = u32::decode(&bytes[11..15]).unwrap();
+    Ok((compression, UVec2::new(width, height), &bytes[HEADER_LEN..]))
+}
+
+fn write_values<T: GridEncode>(values: &[T], compression: Compression, out: &mut Vec<u8>) {
+    match compression {
+        Compression::None => values.iter().for_each(|v| v.encode(out)),
+        Compression::RunLength => {
+            // Values are compared by their encoded bytes so no `PartialEq`
+            // bound is needed.
+            let mut prev = Vec::with_capacity(T::SIZE);
+            let mut curr = Vec::with_capacity(T::SIZE);
+            let mut count: u32 = 0;
+            for v in values {
+                curr.clear();
+                v.encode(&mut curr);
+                if count > 0 && (curr != prev || count == u32::MAX) {
+                    count.encode(out);
+                    out.extend_from_slice(&prev);
+                    count = 0;
+                }
+                std::mem::swap(&mut prev, &mut curr);
+                count += 1;
+            }
+            if count > 0 {
+                count.encode(out);
+                out.extend_from_slice(&prev);
+            }
+        }
+    }
+}
+
+fn read_values<T: GridEncode>(
+    data: &[u8],
+    compression: Compression,
+    len: usize,
+) -> Result<Vec<T>, DecodeError> {
+    assert!(
+        T::SIZE > 0,
+        "Attempting to decode grid values with an encoded size of zero"
+    );
+    match compression {
+        Compression::None => {
+            check_len(
+                data.len(),
+                len.checked_mul(T::SIZE).ok_or(DecodeError::TooLarge)?,
+            )?;
+            data.chunks_exact(T::SIZE)
+                .map(|bytes| T::decode(bytes).ok_or(DecodeError::InvalidValue))
+                .collect()
+        }
+        Compression::RunLength => {
+            let runs = read_runs(data, T::SIZE)?;
+            check_run_len(&runs, len)?;
+            // Values are decoded run by run rather than reserving the full
+            // length up front, so an invalid value is found before the whole
+            // grid is allocated.
+            let mut values = Vec::new();
+            for (count, bytes) in runs {
+                for _ in 0..count {
+                    values.push(T::decode(bytes).ok_or(DecodeError::InvalidValue)?);
+                }
+            }
+            Ok(values)
+        }
+    }
+}
+
+/// Split run length encoded data into each run's count and value bytes.
+fn read_runs(data: &[u8], value_size: usize) -> Result<Vec<(usize, &[u8])>, DecodeError> {
+    let run_size = u32::SIZE + value_size;
+    if !data.len().is_multiple_of(run_size) {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(data
+        .chunks_exact(run_size)
+        .map(|run| {
+            let count = u32::decode(&run[..u32::SIZE]).unwrap() as usize;
+            (count, &run[u32::SIZE..])
+        })
+        .collect())
+}
+
+/// The number of values in a grid of the given size.
+fn tile_count(size: UVec2) -> Result<usize, DecodeError> {
+    (size.x as usize)
+        .checked_mul(size.y as usize)
+        .ok_or(DecodeError::TooLarge)
+}
+
+/// Check the total count of a set of runs against the size of the grid.
+fn check_run_len(runs: &[(usize, &[u8])], expected: usize) -> Result<(), DecodeError> {
+    let found = runs
+        .iter()
+        .try_fold(0usize, |total, (count, _)| total.checked_add(*count))
+        .ok_or(DecodeError::TooLarge)?;
+    if found != expected {
+        return Err(DecodeError::SizeMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Check the length of uncompressed data against the expected length.
+fn check_len(found: usize, expected: usize) -> Result<(), DecodeError> {
+    match found.cmp(&expected) {
+        std::cmp::Ordering::Less => Err(DecodeError::Truncated),
+        std::cmp::Ordering::Equal => Ok(()),
+        std::cmp::Ordering::Greater => Err(DecodeError::SizeMismatch { expected, found }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_round_trip() {
+        let mut grid = Grid::<char>::filled('.', [7, 5]);
+        grid[[3, 2]] = '@';
+        grid[[6, 4]] = '#';
+
+        for compression in [Compression::None, Compression::RunLength] {
+            let bytes = grid.to_bytes(compression);
+            assert_eq!(grid, Grid::from_bytes(&bytes).unwrap());
+        }
+        assert!(
+            grid.to_bytes(Compression::RunLength).len() < grid.to_bytes(Compression::None).len()
+        );
+    }
+
+    #[test]
+    fn bit_grid_round_trip() {
+        let mut grid = BitGrid::new([13, 3]);
+        grid.set_true([0, 0]);
+        grid.set_true([12, 2]);
+        grid.set_true([5, 1]);
+
+        for compression in [Compression::None, Compression::RunLength] {
+            let bytes = grid.to_bytes(compression);
+            let loaded = BitGrid::from_bytes(&bytes).unwrap();
+            assert_eq!(grid.bits(), loaded.bits());
+            assert_eq!(grid.size(), loaded.size());
+        }
+    }
+
+    #[test]
+    fn float_grid_round_trip() {
+        let mut grid = FloatGrid::new([4, 4]);
+        grid.set_value([1, 2], 0.25);
+
+        for compression in [Compression::None, Compression::RunLength] {
+            let bytes = grid.to_bytes(compression);
+            assert_eq!(grid, FloatGrid::from_bytes(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let grid = Grid::<u32>::filled(5, [4, 4]);
+        let bytes = grid.to_bytes(Compression::None);
+
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            Grid::<u32>::from_bytes(&bytes[..bytes.len() - 1])
+        );
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            Grid::<u32>::from_bytes(&bytes[..8])
+        );
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            Err(DecodeError::InvalidMagic),
+            Grid::<u32>::from_bytes(&bad_magic)
+        );
+
+        let mut extra = bytes.clone();
+        extra.extend([0; 4]);
+        assert!(matches!(
+            Grid::<u32>::from_bytes(&extra),
+            Err(DecodeError::SizeMismatch { .. })
+        ));
+
+        assert!(matches!(
+            FloatGrid::from_bytes(&bytes),
+            Err(DecodeError::WrongKind { .. })
+        ));
+
+        let rle = grid.to_bytes(Compression::RunLength);
+        let mut wrong_size = rle.clone();
+        wrong_size[7] = 5;
+        assert_eq!(
+            Err(DecodeError::SizeMismatch {
+                expected: 20,
+                found: 16
+            }),
+            Grid::<u32>::from_bytes(&wrong_size)
+        );
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            Grid::<u32>::from_bytes(&rle[..rle.len() - 2])
+        );
+    }
+
+    #[test]
+    fn huge_size() {
+        // A header claiming the largest possible grid with no data following
+        // it must fail without allocating the grid.
+        for compression in [Compression::None, Compression::RunLength] {
+            let mut bytes = write_header(KIND_GRID, compression, UVec2::MAX);
+            assert!(Grid::<u8>::from_bytes(&bytes).is_err());
+            bytes[5] = KIND_BIT_GRID;
+            assert!(BitGrid::from_bytes(&bytes).is_err());
+            bytes[5] = KIND_FLOAT_GRID;
+            assert!(FloatGrid::from_bytes(&bytes).is_err());
+        }
+
+        // A run count that overflows the grid is rejected before any values
+        // are decoded.
+        let mut bytes = write_header(KIND_GRID, Compression::RunLength, UVec2::new(4, 1));
+        for _ in 0..2 {
+            u32::MAX.encode(&mut bytes);
+            bytes.push(1);
+        }
+        assert!(matches!(
+            Grid::<u8>::from_bytes(&bytes),
+            Err(DecodeError::SizeMismatch { expected: 4, .. } | DecodeError::TooLarge)
+        ));
+    }
+}
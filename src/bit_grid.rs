@@ -3,15 +3,56 @@
 use bit_vec::BitVec;
 use glam::{IVec2, UVec2};
 
-use crate::{GridPoint, GridRect, GridSize, SizedGrid};
+use crate::{grid::clip_blit, GridPoint, GridRect, GridSize, SizedGrid};
 
 /// A rectangular grid with it's underlying data defined as a [BitVec].
 #[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BitGridData"))]
 pub struct BitGrid {
     bits: BitVec,
     size: UVec2,
 }
 
+/// Serialized grid data, with the bits packed into bytes.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BitGridData {
+    bits: Vec<u8>,
+    size: UVec2,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BitGrid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BitGridData {
+            bits: self.bits.to_bytes(),
+            size: self.size,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BitGridData> for BitGrid {
+    type Error = String;
+
+    fn try_from(grid: BitGridData) -> Result<Self, Self::Error> {
+        let BitGridData { bits, size } = grid;
+        let len = size.x as usize * size.y as usize;
+        if bits.len() != len.div_ceil(8) {
+            return Err(format!(
+                "Grid data length of {} bytes doesn't match grid size {}",
+                bits.len(),
+                size
+            ));
+        }
+        let mut bits = BitVec::from_bytes(&bits);
+        bits.truncate(len);
+        Ok(Self { bits, size })
+    }
+}
+
 impl SizedGrid for BitGrid {
     fn size(&self) -> UVec2 {
         self.size
@@ -150,14 +191,173 @@ impl BitGrid {
         rect.iter_points().map(move |p| (p, self.get(p)))
     }
 
+    /// The number of set bits in the grid.
+    pub fn count_ones(&self) -> usize {
+        self.bits
+            .storage()
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    /// Set each bit to the intersection of itself and the corresponding bit
+    /// in another grid of the same size.
+    pub fn and(&mut self, other: &BitGrid) {
+        self.assert_same_size(other);
+        self.bits.and(&other.bits);
+    }
+
+    /// Set each bit to the union of itself and the corresponding bit in
+    /// another grid of the same size.
+    pub fn or(&mut self, other: &BitGrid) {
+        self.assert_same_size(other);
+        self.bits.or(&other.bits);
+    }
+
+    /// Set each bit to the symmetric difference of itself and the
+    /// corresponding bit in another grid of the same size.
+    pub fn xor(&mut self, other: &BitGrid) {
+        self.assert_same_size(other);
+        self.bits.xor(&other.bits);
+    }
+
+    /// Unset each bit that is set in another grid of the same size.
+    pub fn difference(&mut self, other: &BitGrid) {
+        self.assert_same_size(other);
+        self.bits.difference(&other.bits);
+    }
+
+    /// Combine another grid into this one with it's bottom left bit at the
+    /// given offset. Bits which don't overlap this grid are ignored.
+    pub fn or_offset(&mut self, other: &BitGrid, offset: impl GridPoint) {
+        let offset = offset.to_ivec2();
+        let min = offset.max(IVec2::ZERO);
+        let max = (offset + other.size.as_ivec2()).min(self.size.as_ivec2());
+        if max.cmple(min).any() {
+            return;
+        }
+        let len = (max.x - min.x) as usize;
+        let (width, other_width) = (self.width(), other.width());
+        let src = other.bits.storage();
+        self.with_blocks_mut(|dst| {
+            for y in min.y..max.y {
+                let src_start = (y - offset.y) as usize * other_width + (min.x - offset.x) as usize;
+                let dst_start = y as usize * width + min.x as usize;
+                for x in (0..len).step_by(64) {
+                    let n = (len - x).min(64);
+                    let bits = read_block_bits(src, src_start + x, n);
+                    let current = read_block_bits(dst, dst_start + x, n);
+                    write_block_bits(dst, dst_start + x, n, current | bits);
+                }
+            }
+        });
+    }
+
+    /// Modify the raw blocks of the grid's bits, for operations that work on
+    /// many bits at once.
+    ///
+    /// Any bits past the end of the grid in the last block are cleared once
+    /// `f` returns, or if it panics.
+    pub(crate) fn with_blocks_mut<R>(&mut self, f: impl FnOnce(&mut [u32]) -> R) -> R {
+        /// Clears the unused bits of the last block when dropped.
+        struct ClearTail<'a> {
+            blocks: &'a mut Vec<u32>,
+            len: usize,
+        }
+
+        impl Drop for ClearTail<'_> {
+            fn drop(&mut self) {
+                let used = self.len % 32;
+                if let (Some(last), true) = (self.blocks.last_mut(), used != 0) {
+                    *last &= low_bits(used) as u32;
+                }
+            }
+        }
+
+        let len = self.bits.len();
+        // SAFETY: BitVec relies on it's storage holding exactly enough blocks
+        // for it's length, with every bit past the length unset. `f` is only
+        // given a slice so it can't add or remove blocks, and the guard clears
+        // any bits it sets past the length before the borrow of the storage
+        // ends, including when unwinding.
+        let guard = ClearTail {
+            blocks: unsafe { self.bits.storage_mut() },
+            len,
+        };
+        f(guard.blocks)
+    }
+
+    fn assert_same_size(&self, other: &BitGrid) {
+        assert_eq!(
+            self.size, other.size,
+            "Attempting to combine BitGrids of different sizes"
+        );
+    }
+
     /// Create a new BitGrid from a rectangular area within this grid.
+    ///
+    /// The bottom left of the area becomes the bottom left of the new grid.
     pub fn clone_rect(&self, area: GridRect) -> BitGrid {
         let mut grid = BitGrid::new(area.size());
         for p in area.iter_points() {
-            grid.set(p, self.get(p));
+            grid.set(p - area.pos, self.get(p));
         }
         grid
     }
+
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`.
+    ///
+    /// The area is clipped to the bounds of both grids.
+    pub fn blit(&mut self, src: &BitGrid, src_rect: GridRect, dest_pos: impl GridPoint) {
+        self.blit_with(src, src_rect, dest_pos, |_, b| b);
+    }
+
+    /// Combine a rectangular area of another grid with this one, with the
+    /// bottom left of the area placed at `dest_pos`.
+    ///
+    /// `blend` is called with the existing value and the value from `src`. The
+    /// area is clipped to the bounds of both grids.
+    pub fn blit_with(
+        &mut self,
+        src: &BitGrid,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        blend: impl Fn(bool, bool) -> bool,
+    ) {
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points() {
+            let d = p + offset;
+            self.set(d, blend(self.get(d), src.get(p)));
+        }
+    }
+
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`. Only bits where the bit at the
+    /// same position in `mask` is set are copied.
+    ///
+    /// The mask must be the same size as `src`. The area is clipped to the
+    /// bounds of both grids.
+    pub fn blit_masked(
+        &mut self,
+        src: &BitGrid,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        mask: &BitGrid,
+    ) {
+        assert_eq!(
+            src.size, mask.size,
+            "Attempting to blit with a mask of a different size than the source grid"
+        );
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points().filter(|p| mask.get(*p)) {
+            self.set(p + offset, src.get(p));
+        }
+    }
 }
 
 impl IntoIterator for BitGrid {
@@ -197,10 +397,116 @@ impl std::ops::Index<usize> for BitGrid {
     }
 }
 
+macro_rules! impl_bit_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident, $method:ident) => {
+        impl std::ops::$assign_op<&BitGrid> for BitGrid {
+            fn $assign_fn(&mut self, rhs: &BitGrid) {
+                self.$method(rhs);
+            }
+        }
+
+        impl std::ops::$assign_op for BitGrid {
+            fn $assign_fn(&mut self, rhs: BitGrid) {
+                self.$method(&rhs);
+            }
+        }
+
+        impl std::ops::$op<&BitGrid> for &BitGrid {
+            type Output = BitGrid;
+
+            fn $fn(self, rhs: &BitGrid) -> BitGrid {
+                let mut grid = self.clone();
+                grid.$method(rhs);
+                grid
+            }
+        }
+
+        impl std::ops::$op for BitGrid {
+            type Output = BitGrid;
+
+            fn $fn(mut self, rhs: BitGrid) -> BitGrid {
+                self.$method(&rhs);
+                self
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, and);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, or);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, xor);
+
+impl std::ops::Not for BitGrid {
+    type Output = BitGrid;
+
+    fn not(mut self) -> BitGrid {
+        self.all_negate();
+        self
+    }
+}
+
+impl std::ops::Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        !self.clone()
+    }
+}
+
+/// Read up to 64 bits from a list of blocks starting at the given bit index.
+pub(crate) fn read_block_bits(blocks: &[u32], start: usize, len: usize) -> u64 {
+    let mut out = 0;
+    let mut read = 0;
+    while read < len {
+        let i = start + read;
+        let n = (32 - i % 32).min(len - read);
+        let bits = (blocks[i / 32] >> (i % 32)) as u64 & low_bits(n);
+        out |= bits << read;
+        read += n;
+    }
+    out
+}
+
+/// Write up to 64 bits into a list of blocks starting at the given bit index.
+pub(crate) fn write_block_bits(blocks: &mut [u32], start: usize, len: usize, value: u64) {
+    let mut written = 0;
+    while written < len {
+        let i = start + written;
+        let n = (32 - i % 32).min(len - written);
+        let mask = (low_bits(n) as u32) << (i % 32);
+        let bits = ((value >> written) as u32) << (i % 32);
+        blocks[i / 32] = (blocks[i / 32] & !mask) | (bits & mask);
+        written += n;
+    }
+}
+
+/// A mask of the lowest `len` bits.
+#[inline]
+pub(crate) fn low_bits(len: usize) -> u64 {
+    if len >= 64 {
+        u64::MAX
+    } else {
+        (1 << len) - 1
+    }
+}
+
+/// A grid of pseudo random bits for tests, with roughly one in `one_in` bits
+/// set. The same seed always gives the same grid.
+#[cfg(test)]
+pub(crate) fn noise(size: impl GridSize, seed: u32, one_in: u32) -> BitGrid {
+    let mut grid = BitGrid::new(size);
+    let mut state = seed;
+    for i in 0..grid.bits.len() {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        grid.set_index(i, (state >> 16).is_multiple_of(one_in));
+    }
+    grid
+}
+
 #[cfg(test)]
 mod tests {
-    use super::BitGrid;
-    use crate::SizedGrid;
+    use super::{noise, BitGrid};
+    use crate::{GridRect, SizedGrid};
 
     #[test]
     fn iter() {
@@ -217,4 +523,152 @@ mod tests {
         assert!(points[grid.transform_lti([9, 2])].1);
         assert!(points[grid.transform_lti([9, 4])].1);
     }
+
+    #[test]
+    fn ops() {
+        let mut a = BitGrid::new([40, 3]);
+        let mut b = BitGrid::new([40, 3]);
+        a.set_true([0, 0]);
+        a.set_true([35, 2]);
+        b.set_true([35, 2]);
+        b.set_true([10, 1]);
+
+        assert_eq!(1, (&a & &b).count_ones());
+        assert_eq!(3, (&a | &b).count_ones());
+        assert_eq!(2, (&a ^ &b).count_ones());
+        assert_eq!(118, (!&a).count_ones());
+
+        let mut c = a.clone();
+        c.difference(&b);
+        assert!(c.get([0, 0]));
+        assert_eq!(1, c.count_ones());
+
+        a |= &b;
+        assert_eq!(3, a.count_ones());
+    }
+
+    #[test]
+    fn clone_rect_offset() {
+        // Bits are placed relative to the rect, not at their position in the
+        // source grid.
+        let mut grid = BitGrid::new([6, 5]);
+        grid.set_true([2, 3]);
+        grid.set_true([4, 4]);
+        grid.set_true([1, 1]);
+        let rect = grid.clone_rect(GridRect::new([2, 3], [3, 2]));
+        assert_eq!([3, 2], rect.size().to_array());
+        assert_eq!(2, rect.count_ones());
+        assert!(rect.get([0, 0]));
+        assert!(rect.get([2, 1]));
+    }
+
+    #[test]
+    fn blit() {
+        let mut src = BitGrid::new([3, 3]);
+        src.set_true([1, 1]);
+        src.set_true([2, 2]);
+        assert_eq!(
+            vec![true, false, false, true],
+            src.clone_rect(GridRect::new([1, 1], [2, 2]))
+                .iter()
+                .collect::<Vec<_>>()
+        );
+
+        let mut grid = BitGrid::new([4, 4]).with_value(true);
+        grid.blit(&src, GridRect::new([1, 1], [2, 2]), [3, 3]);
+        assert_eq!(16, grid.count_ones());
+        grid.blit(&src, GridRect::new([0, 0], [2, 2]), [-1, -1]);
+        assert_eq!(16, grid.count_ones());
+        grid.blit(&src, GridRect::new([0, 0], [2, 2]), [1, 0]);
+        assert_eq!(13, grid.count_ones());
+        assert!(!grid.get([1, 0]));
+        assert!(grid.get([2, 1]));
+
+        grid.blit_with(&src, GridRect::new([0, 0], [3, 3]), [1, 1], |a, b| a && !b);
+        assert!(!grid.get([2, 2]));
+        assert!(grid.get([2, 3]));
+
+        let mask = src.clone();
+        grid.blit_masked(
+            &BitGrid::new([3, 3]),
+            GridRect::new([0, 0], [3, 3]),
+            [0, 0],
+            &mask,
+        );
+        assert!(!grid.get([1, 1]));
+        assert!(grid.get([1, 2]));
+    }
+
+    #[test]
+    fn or_offset() {
+        let mut grid = BitGrid::new([70, 4]);
+        let stamp = BitGrid::new([68, 2]).with_value(true);
+
+        grid.or_offset(&stamp, [-1, 3]);
+        assert_eq!(67, grid.count_ones());
+        assert!(grid.get([0, 3]));
+        assert!(grid.get([66, 3]));
+        assert!(!grid.get([67, 3]));
+
+        grid.or_offset(&stamp, [10, -10]);
+        grid.or_offset(&stamp, [70, 0]);
+        assert_eq!(67, grid.count_ones());
+
+        grid.or_offset(&stamp, [5, 0]);
+        assert_eq!(67 + 65 * 2, grid.count_ones());
+        assert!(!grid.get([4, 0]));
+        assert!(grid.get([69, 1]));
+    }
+
+    #[test]
+    fn blocks_mut_clears_tail() {
+        let mut grid = BitGrid::new([7, 5]);
+        grid.with_blocks_mut(|blocks| blocks.fill(u32::MAX));
+        assert_eq!(35, grid.count_ones());
+        assert!(grid.bits() == BitGrid::new([7, 5]).with_value(true).bits());
+    }
+
+    #[test]
+    fn or_offset_matches_reference() {
+        // Widths that aren't a multiple of the block size leave unused bits
+        // in the last block, which must stay unset.
+        for width in [1, 5, 31, 33, 47, 64, 65, 70, 97] {
+            let base = noise([width, 5], width, 3);
+            let other = noise([width + 3, 4], width + 1, 2);
+            for offset in [[0, 0], [-2, 1], [3, -1], [width as i32 - 2, 3]] {
+                let mut grid = base.clone();
+                grid.or_offset(&other, offset);
+
+                let mut reference = base.clone();
+                for (p, bit) in other.iter_xy() {
+                    let p = p + glam::IVec2::from(offset);
+                    if bit && reference.in_bounds(p) {
+                        reference.set_true(p);
+                    }
+                }
+                assert!(reference.bits() == grid.bits(), "{width} {offset:?}");
+                assert_eq!(
+                    grid.iter().filter(|b| *b).count(),
+                    grid.count_ones(),
+                    "{width} {offset:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = BitGrid::new([5, 3]);
+        grid.set_true([0, 0]);
+        grid.set_true([4, 2]);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: BitGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(grid.bits(), loaded.bits());
+        assert_eq!(grid.size(), loaded.size());
+
+        let wrong_size = r#"{"bits":[255],"size":[5,3]}"#;
+        assert!(serde_json::from_str::<BitGrid>(wrong_size).is_err());
+    }
 }
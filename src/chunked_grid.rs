@@ -0,0 +1,379 @@
+//! An unbounded grid made up of fixed size [Grid] chunks.
+//!
+//! Chunks are created on demand when values are inserted and can be unloaded
+//! at any time. Positions can be negative, and are converted between world,
+//! chunk and local coordinates:
+//!
+//! - World coordinates are the position of a tile in the unbounded grid.
+//! - Chunk coordinates are the position of a chunk, where a chunk at `[1,0]`
+//!   starts at world position `[chunk_width, 0]`.
+//! - Local coordinates are the position of a tile inside its chunk.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::ChunkedGrid;
+//!
+//! let mut grid = ChunkedGrid::new([16, 16]);
+//!
+//! grid.insert([-1, 20], 'a');
+//!
+//! assert_eq!(Some(&'a'), grid.get([-1, 20]));
+//! assert_eq!([-1, 1], grid.world_to_chunk([-1, 20]).to_array());
+//! assert_eq!([15, 4], grid.world_to_local([-1, 20]).to_array());
+//! assert_eq!(1, grid.chunk_count());
+//! ```
+
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use glam::{IVec2, UVec2};
+
+use crate::{Grid, GridPoint, GridRect, GridSize, SizedGrid};
+
+/// An unbounded grid made up of fixed size [Grid] chunks.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ChunkedGridData<T>"))]
+pub struct ChunkedGrid<T> {
+    chunks: HashMap<IVec2, Grid<T>>,
+    chunk_size: UVec2,
+}
+
+/// Serialized grid data, with each chunk stored alongside it's position.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ChunkedGridData<T> {
+    chunks: Vec<(IVec2, Grid<T>)>,
+    chunk_size: UVec2,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<ChunkedGridData<T>> for ChunkedGrid<T> {
+    type Error = String;
+
+    fn try_from(grid: ChunkedGridData<T>) -> Result<Self, Self::Error> {
+        let ChunkedGridData { chunks, chunk_size } = grid;
+        if !chunk_size.cmpgt(UVec2::ZERO).all() {
+            return Err(format!(
+                "Chunk size {} is empty, every chunk must contain at least one tile",
+                chunk_size
+            ));
+        }
+        let mut out = HashMap::with_capacity(chunks.len());
+        for (p, chunk) in chunks {
+            if chunk.size() != chunk_size {
+                return Err(format!(
+                    "Chunk {} has size {} but the grid's chunk size is {}",
+                    p,
+                    chunk.size(),
+                    chunk_size
+                ));
+            }
+            if out.insert(p, chunk).is_some() {
+                return Err(format!("Chunk {} appears more than once", p));
+            }
+        }
+        Ok(Self {
+            chunks: out,
+            chunk_size,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for ChunkedGrid<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("ChunkedGrid", 2)?;
+        state.serialize_field("chunks", &self.iter_chunks().collect::<Vec<_>>())?;
+        state.serialize_field("chunk_size", &self.chunk_size)?;
+        state.end()
+    }
+}
+
+impl<T> ChunkedGrid<T> {
+    /// Create an empty [ChunkedGrid] where each chunk is the given size.
+    pub fn new(chunk_size: impl GridSize) -> Self {
+        let chunk_size = chunk_size.to_uvec2();
+        assert!(
+            chunk_size.cmpgt(UVec2::ZERO).all(),
+            "Attempting to create a chunked grid with an empty chunk size {}",
+            chunk_size
+        );
+        Self {
+            chunks: HashMap::new(),
+            chunk_size,
+        }
+    }
+
+    /// The size of each chunk.
+    pub fn chunk_size(&self) -> UVec2 {
+        self.chunk_size
+    }
+
+    /// Convert a world position to the position of the chunk containing it.
+    #[inline]
+    pub fn world_to_chunk(&self, xy: impl GridPoint) -> IVec2 {
+        xy.to_ivec2().div_euclid(self.chunk_size.as_ivec2())
+    }
+
+    /// Convert a world position to a local position inside it's chunk.
+    #[inline]
+    pub fn world_to_local(&self, xy: impl GridPoint) -> IVec2 {
+        xy.to_ivec2().rem_euclid(self.chunk_size.as_ivec2())
+    }
+
+    /// Convert a chunk position and a local position inside that chunk to a
+    /// world position.
+    #[inline]
+    pub fn chunk_to_world(&self, chunk: impl GridPoint, local: impl GridPoint) -> IVec2 {
+        chunk.to_ivec2() * self.chunk_size.as_ivec2() + local.to_ivec2()
+    }
+
+    /// The area of a chunk in world coordinates.
+    pub fn chunk_bounds(&self, chunk: impl GridPoint) -> GridRect {
+        GridRect::new(self.chunk_to_world(chunk, [0, 0]), self.chunk_size)
+    }
+
+    /// Try to retrieve the value at the given world position.
+    ///
+    /// Returns `None` if the chunk containing the position isn't loaded.
+    pub fn get(&self, xy: impl GridPoint) -> Option<&T> {
+        let chunk = self.chunks.get(&self.world_to_chunk(xy))?;
+        chunk.get(self.world_to_local(xy))
+    }
+
+    /// Try to retrieve the mutable value at the given world position.
+    ///
+    /// Returns `None` if the chunk containing the position isn't loaded.
+    pub fn get_mut(&mut self, xy: impl GridPoint) -> Option<&mut T> {
+        let local = self.world_to_local(xy);
+        let chunk = self.chunks.get_mut(&self.world_to_chunk(xy))?;
+        chunk.get_mut(local)
+    }
+
+    /// Set the value at the given world position, creating the chunk
+    /// containing it if it isn't loaded.
+    pub fn insert(&mut self, xy: impl GridPoint, value: T)
+    where
+        T: Default + Clone,
+    {
+        let local = self.world_to_local(xy);
+        let chunk = self.chunk_or_create(self.world_to_chunk(xy));
+        chunk[local] = value;
+    }
+
+    /// Retrieve the chunk at the given chunk position if it's loaded.
+    pub fn chunk(&self, chunk: impl GridPoint) -> Option<&Grid<T>> {
+        self.chunks.get(&chunk.to_ivec2())
+    }
+
+    /// Retrieve the mutable chunk at the given chunk position if it's loaded.
+    pub fn chunk_mut(&mut self, chunk: impl GridPoint) -> Option<&mut Grid<T>> {
+        self.chunks.get_mut(&chunk.to_ivec2())
+    }
+
+    /// Retrieve the chunk at the given chunk position, creating it with
+    /// default values if it isn't loaded.
+    pub fn chunk_or_create(&mut self, chunk: impl GridPoint) -> &mut Grid<T>
+    where
+        T: Default + Clone,
+    {
+        let size = self.chunk_size;
+        self.chunks
+            .entry(chunk.to_ivec2())
+            .or_insert_with(|| Grid::new(size))
+    }
+
+    /// Insert a chunk at the given chunk position, returning the previously
+    /// loaded chunk if there was one.
+    ///
+    /// The chunk must be the same size as [ChunkedGrid::chunk_size].
+    pub fn insert_chunk(&mut self, chunk: impl GridPoint, grid: Grid<T>) -> Option<Grid<T>> {
+        assert_eq!(
+            self.chunk_size,
+            grid.size(),
+            "Attempting to insert a chunk with the wrong size"
+        );
+        self.chunks.insert(chunk.to_ivec2(), grid)
+    }
+
+    /// Unload the chunk at the given chunk position, returning it if it was
+    /// loaded.
+    pub fn unload_chunk(&mut self, chunk: impl GridPoint) -> Option<Grid<T>> {
+        self.chunks.remove(&chunk.to_ivec2())
+    }
+
+    /// Unload all chunks.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Returns true if the chunk at the given chunk position is loaded.
+    pub fn is_loaded(&self, chunk: impl GridPoint) -> bool {
+        self.chunks.contains_key(&chunk.to_ivec2())
+    }
+
+    /// The number of loaded chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Iterate over all loaded chunks along with their chunk positions.
+    ///
+    /// Chunks are visited in an arbitrary order.
+    pub fn iter_chunks(&self) -> impl Iterator<Item = (IVec2, &Grid<T>)> {
+        self.chunks.iter().map(|(p, chunk)| (*p, chunk))
+    }
+
+    /// Iterate over all loaded chunks along with their chunk positions.
+    ///
+    /// Chunks are visited in an arbitrary order.
+    pub fn iter_chunks_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut Grid<T>)> {
+        self.chunks.iter_mut().map(|(p, chunk)| (*p, chunk))
+    }
+
+    /// Iterate over the values in all loaded chunks along with their world
+    /// positions.
+    ///
+    /// Chunks are visited in an arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        let size = self.chunk_size.as_ivec2();
+        self.chunks.iter().flat_map(move |(chunk_pos, chunk)| {
+            let origin = *chunk_pos * size;
+            chunk.iter_xy().map(move |(p, v)| (origin + p, v))
+        })
+    }
+
+    /// Iterate over the values in all loaded chunks along with their world
+    /// positions.
+    ///
+    /// Chunks are visited in an arbitrary order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut T)> {
+        let size = self.chunk_size.as_ivec2();
+        self.chunks.iter_mut().flat_map(move |(chunk_pos, chunk)| {
+            let origin = *chunk_pos * size;
+            chunk.iter_xy_mut().map(move |(p, v)| (origin + p, v))
+        })
+    }
+}
+
+impl<T, P: GridPoint> Index<P> for ChunkedGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: P) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T, P: GridPoint> IndexMut<P> for ChunkedGrid<T>
+where
+    T: Default + Clone,
+{
+    fn index_mut(&mut self, index: P) -> &mut Self::Output {
+        let local = self.world_to_local(index);
+        let chunk = self.chunk_or_create(self.world_to_chunk(index));
+        &mut chunk[local]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        let grid = ChunkedGrid::<u8>::new([8, 4]);
+
+        assert_eq!([0, 0], grid.world_to_chunk([7, 3]).to_array());
+        assert_eq!([1, 1], grid.world_to_chunk([8, 4]).to_array());
+        assert_eq!([-1, -1], grid.world_to_chunk([-1, -1]).to_array());
+        assert_eq!([-2, -1], grid.world_to_chunk([-9, -4]).to_array());
+
+        assert_eq!([7, 3], grid.world_to_local([-1, -1]).to_array());
+        assert_eq!([7, 0], grid.world_to_local([-9, -4]).to_array());
+
+        for p in [[-9, -4], [-1, -1], [0, 0], [17, 5]] {
+            let world = grid.chunk_to_world(grid.world_to_chunk(p), grid.world_to_local(p));
+            assert_eq!(p, world.to_array());
+        }
+    }
+
+    #[test]
+    fn insert_get() {
+        let mut grid = ChunkedGrid::new([4, 4]);
+        grid.insert([-5, 3], 1);
+        grid.insert([100, -100], 2);
+        grid[[-6, 3]] = 3;
+
+        assert_eq!(Some(&1), grid.get([-5, 3]));
+        assert_eq!(Some(&2), grid.get([100, -100]));
+        assert_eq!(3, grid[[-6, 3]]);
+        assert_eq!(Some(&0), grid.get([-8, 0]));
+        assert_eq!(None, grid.get([0, 0]));
+        assert_eq!(2, grid.chunk_count());
+
+        *grid.get_mut([-5, 3]).unwrap() += 10;
+        assert_eq!(11, grid[[-5, 3]]);
+    }
+
+    #[test]
+    fn unload() {
+        let mut grid = ChunkedGrid::new([4, 4]);
+        grid.insert([1, 1], 'a');
+        grid.insert([-1, -1], 'b');
+
+        let chunk = grid.unload_chunk([-1, -1]).unwrap();
+        assert_eq!('b', chunk[[3, 3]]);
+        assert!(!grid.is_loaded([-1, -1]));
+        assert_eq!(None, grid.get([-1, -1]));
+
+        grid.insert_chunk([5, 5], chunk);
+        assert_eq!(Some(&'b'), grid.get([23, 23]));
+    }
+
+    #[test]
+    fn iter() {
+        let mut grid = ChunkedGrid::new([2, 2]);
+        grid.insert([-1, -1], 1);
+        grid.insert([2, 3], 2);
+
+        let mut set: Vec<_> = grid.iter().filter(|(_, v)| **v != 0).collect();
+        set.sort_by_key(|(p, _)| p.x);
+        assert_eq!((IVec2::new(-1, -1), &1), set[0]);
+        assert_eq!((IVec2::new(2, 3), &2), set[1]);
+        assert_eq!(8, grid.iter().count());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = ChunkedGrid::new([2, 2]);
+        grid.insert([-1, -1], 1);
+        grid.insert([2, 3], 2);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: ChunkedGrid<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(grid, loaded);
+
+        let wrong_size = r#"{"chunks":[[[0,0],{"data":[1],"size":[1,1]}]],"chunk_size":[2,2]}"#;
+        assert!(serde_json::from_str::<ChunkedGrid<i32>>(wrong_size).is_err());
+        let empty_size = r#"{"chunks":[],"chunk_size":[0,0]}"#;
+        assert!(serde_json::from_str::<ChunkedGrid<i32>>(empty_size).is_err());
+        let empty_height = r#"{"chunks":[],"chunk_size":[4,0]}"#;
+        assert!(serde_json::from_str::<ChunkedGrid<i32>>(empty_height).is_err());
+
+        let chunk = r#"{"data":[1,2,3,4],"size":[2,2]}"#;
+        let duplicate = format!(
+            r#"{{"chunks":[[[0,0],{}],[[0,0],{}]],"chunk_size":[2,2]}}"#,
+            chunk, chunk
+        );
+        let err = serde_json::from_str::<ChunkedGrid<i32>>(&duplicate)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("more than once"));
+    }
+}
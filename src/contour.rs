@@ -0,0 +1,206 @@
+//! Contour extraction using marching squares, for turning heightmaps and masks
+//! into outlines for collision or rendering.
+//!
+//! Each value of a grid is a sample at it's grid position, and contours are
+//! traced between samples. Positions outside the grid are treated as below the
+//! level, so every contour is a closed loop. Outer boundaries wind
+//! counter-clockwise and holes wind clockwise.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{contour, BitGrid};
+//!
+//! let mut grid = BitGrid::new([5, 5]);
+//! for p in [[1, 1], [2, 1], [3, 1], [1, 2], [3, 2], [1, 3], [2, 3], [3, 3]] {
+//!     grid.set_true(p);
+//! }
+//!
+//! let contours = contour::trace_bits(&grid);
+//! assert_eq!(2, contours.len());
+//! assert_eq!(1, contours.iter().filter(|c| c.is_hole).count());
+//! ```
+
+use glam::{IVec2, UVec2, Vec2};
+
+use crate::{BitGrid, FloatGrid, SizedGrid};
+
+/// A closed loop traced around the edge of a region.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contour {
+    /// The points of the loop. The last point connects back to the first.
+    pub points: Vec<Vec2>,
+    /// Whether the loop is the boundary of a hole inside a region.
+    pub is_hole: bool,
+}
+
+impl Contour {
+    /// The area enclosed by the loop, negative if the loop winds clockwise.
+    pub fn signed_area(&self) -> f32 {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| self.points[i].perp_dot(self.points[(i + 1) % n]))
+            .sum::<f32>()
+            * 0.5
+    }
+}
+
+/// Trace the contours around every region of values greater than or equal to
+/// `level`.
+///
+/// Contour points are interpolated between samples. Saddle points are resolved
+/// as inside if the average of the four surrounding samples is above the
+/// level.
+pub fn trace(grid: &FloatGrid, level: f32) -> Vec<Contour> {
+    trace_by(grid.size(), level, |p| grid.get_value(p))
+}
+
+/// Trace the contours around every region of set bits.
+///
+/// Contour points lie halfway between samples. Diagonally adjacent bits are
+/// treated as separate regions.
+pub fn trace_bits(grid: &BitGrid) -> Vec<Contour> {
+    trace_by(grid.size(), 0.5, |p| {
+        grid.in_bounds(p).then(|| grid.get(p) as u8 as f32)
+    })
+}
+
+/// Marching squares over a grid of samples padded by one position on every
+/// side, where `value` returns [None] for positions outside the grid.
+fn trace_by(size: UVec2, level: f32, value: impl Fn(IVec2) -> Option<f32>) -> Vec<Contour> {
+    const NONE: usize = usize::MAX;
+    // Corners of a cell in counter-clockwise order from the bottom left.
+    const CORNERS: [IVec2; 4] = [
+        IVec2::new(0, 0),
+        IVec2::new(1, 0),
+        IVec2::new(1, 1),
+        IVec2::new(0, 1),
+    ];
+    // The edges between each pair of corners, as a canonical start and end.
+    const EDGES: [(usize, usize); 4] = [(0, 1), (1, 2), (3, 2), (0, 3)];
+
+    let padded = size.as_ivec2() + 2;
+    // Each sample position owns the edges to it's right and above it.
+    let edge_index = |a: IVec2, b: IVec2| {
+        let i = (a.y + 1) * padded.x + a.x + 1;
+        i as usize * 2 + (a.y == b.y) as usize
+    };
+    let mut next = vec![NONE; padded.x as usize * padded.y as usize * 2];
+    let mut points = vec![Vec2::ZERO; next.len()];
+
+    for y in -1..size.y as i32 {
+        for x in -1..size.x as i32 {
+            let cell = IVec2::new(x, y);
+            let values = CORNERS.map(|c| value(cell + c));
+            let inside = values.map(|v| v.is_some_and(|v| v >= level));
+            if inside.iter().all(|b| *b == inside[0]) {
+                continue;
+            }
+
+            let mut edges = [NONE; 4];
+            for (k, &(a, b)) in EDGES.iter().enumerate() {
+                if inside[a] == inside[b] {
+                    continue;
+                }
+                let t = match (values[a], values[b]) {
+                    (Some(a), Some(b)) => (level - a) / (b - a),
+                    _ => 0.5,
+                };
+                let t = if t.is_nan() { 0.5 } else { t.clamp(0.0, 1.0) };
+                let (a, b) = (cell + CORNERS[a], cell + CORNERS[b]);
+                edges[k] = edge_index(a, b);
+                points[edges[k]] = a.as_vec2().lerp(b.as_vec2(), t);
+            }
+
+            // Segments leave the cell where the counter-clockwise walk around
+            // it goes from inside to outside, keeping the inside on their left.
+            let saddle = inside[0] == inside[2] && inside[1] == inside[3];
+            let center_inside =
+                saddle && values.iter().map(|v| v.unwrap_or(0.0)).sum::<f32>() / 4.0 > level;
+            for k in (0..4).filter(|&k| inside[k] && !inside[(k + 1) % 4]) {
+                let entry = (1..4)
+                    .map(|i| match center_inside {
+                        true => (k + i) % 4,
+                        false => (k + 4 - i) % 4,
+                    })
+                    .find(|&j| !inside[j] && inside[(j + 1) % 4])
+                    .unwrap();
+                next[edges[k]] = edges[entry];
+            }
+        }
+    }
+
+    let mut visited = vec![false; next.len()];
+    let mut contours = Vec::new();
+    for start in 0..next.len() {
+        if next[start] == NONE || visited[start] {
+            continue;
+        }
+        let mut loop_points = Vec::new();
+        let mut edge = start;
+        while !visited[edge] {
+            visited[edge] = true;
+            loop_points.push(points[edge]);
+            edge = next[edge];
+        }
+        let mut contour = Contour {
+            points: loop_points,
+            is_hole: false,
+        };
+        contour.is_hole = contour.signed_area() < 0.0;
+        contours.push(contour);
+    }
+    contours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_bit() {
+        let mut grid = BitGrid::new([3, 3]);
+        grid.set_true([1, 1]);
+        let contours = trace_bits(&grid);
+        assert_eq!(1, contours.len());
+        assert_eq!(4, contours[0].points.len());
+        assert_eq!(0.5, contours[0].signed_area());
+        assert!(!contours[0].is_hole);
+
+        grid.set_true([2, 2]);
+        assert_eq!(2, trace_bits(&grid).len());
+        assert!(trace_bits(&BitGrid::new([3, 3])).is_empty());
+    }
+
+    #[test]
+    fn holes() {
+        let mut grid = BitGrid::new([3, 3]).with_value(true);
+        grid.set_false([1, 1]);
+        let contours = trace_bits(&grid);
+        assert_eq!(2, contours.len());
+        let hole = contours.iter().find(|c| c.is_hole).unwrap();
+        assert_eq!(-0.5, hole.signed_area());
+        let outer = contours.iter().find(|c| !c.is_hole).unwrap();
+        assert_eq!(8.5, outer.signed_area());
+    }
+
+    #[test]
+    fn interpolated() {
+        let mut grid = FloatGrid::new([3, 1]);
+        grid.set_value([1, 0], 1.0);
+        let contours = trace(&grid, 0.25);
+        assert_eq!(1, contours.len());
+        let points = &contours[0].points;
+        assert!(points.contains(&Vec2::new(0.25, 0.0)));
+        assert!(points.contains(&Vec2::new(1.75, 0.0)));
+        assert!(points.contains(&Vec2::new(1.0, 0.5)));
+
+        // The average of a saddle is above the level, so the corners join.
+        let mut grid = FloatGrid::new([2, 2]);
+        grid.set_value([0, 0], 1.0);
+        grid.set_value([1, 1], 1.0);
+        assert_eq!(1, trace(&grid, 0.4).len());
+        assert_eq!(2, trace(&grid, 0.6).len());
+    }
+}
@@ -0,0 +1,289 @@
+//! Utilities for building and navigating
+//! ["dijkstra maps"](http://www.roguebasin.com/index.php/The_Incredible_Power_of_Dijkstra_Maps).
+//!
+//! A dijkstra map is a [FloatGrid] where each value is the cost of moving from
+//! that position to the nearest goal. Agents can approach the goals by moving
+//! downhill, or flee from them by moving uphill. Positions which can't reach
+//! any goal are set to [f32::INFINITY].
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{dijkstra_map, direction::{Dir4, Dir8}, BitGrid};
+//!
+//! let walkable = BitGrid::new([10, 10]).with_value(true);
+//! let map = dijkstra_map::from_bit_grid::<Dir4>(&walkable, [[5, 5]]);
+//!
+//! assert_eq!(0.0, map.value([5, 5]));
+//! assert_eq!(10.0, map.value([0, 0]));
+//! assert_eq!(Some(Dir8::Up), dijkstra_map::downhill::<Dir4>(&map, [5, 0]));
+//! ```
+
+use std::collections::BinaryHeap;
+
+use glam::IVec2;
+
+use crate::{
+    direction::{Adjacency, Dir8},
+    pathfinding::{self, Node},
+    BitGrid, FloatGrid, GridPoint, GridSize, SizedGrid,
+};
+
+/// Build a dijkstra map from one or more goal positions.
+///
+/// `cost` should return the cost of moving into the given position, or [None]
+/// if the position can't be entered.
+pub fn build<D: Adjacency>(
+    size: impl GridSize,
+    goals: impl IntoIterator<Item = impl GridPoint>,
+    cost: impl Fn(IVec2) -> Option<f32>,
+) -> FloatGrid {
+    let mut map = FloatGrid::new(size);
+    build_into::<D>(&mut map, goals, cost);
+    map
+}
+
+/// Build a dijkstra map from one or more goal positions, where set bits of
+/// the [BitGrid] are walkable and every move costs `1.0`.
+pub fn from_bit_grid<D: Adjacency>(
+    walkable: &BitGrid,
+    goals: impl IntoIterator<Item = impl GridPoint>,
+) -> FloatGrid {
+    build::<D>(walkable.size(), goals, pathfinding::walkable(walkable))
+}
+
+/// Fill an existing [FloatGrid] with a dijkstra map built from one or more
+/// goal positions. All previous values in the grid are overwritten.
+///
+/// `cost` should return the cost of moving into the given position, or [None]
+/// if the position can't be entered.
+pub fn build_into<D: Adjacency>(
+    map: &mut FloatGrid,
+    goals: impl IntoIterator<Item = impl GridPoint>,
+    cost: impl Fn(IVec2) -> Option<f32>,
+) {
+    map.set_all(f32::INFINITY);
+    let mut open = BinaryHeap::new();
+
+    for goal in goals {
+        if let Some(i) = map.try_transform_lti(goal.to_ivec2()) {
+            map[i] = 0.0;
+            open.push(Node::new(i, 0.0, 0.0));
+        }
+    }
+
+    while let Some(node) = open.pop() {
+        if node.cost > map[node.index] {
+            continue;
+        }
+        let p = map.transform_itl(node.index);
+        for dir in D::directions() {
+            let next = p + *dir;
+            let Some(next_i) = map.try_transform_lti(next) else {
+                continue;
+            };
+            let Some(step) = cost(next) else {
+                continue;
+            };
+            let next_cost = node.cost + step;
+            if next_cost < map[next_i] {
+                map[next_i] = next_cost;
+                open.push(Node::new(next_i, next_cost, next_cost));
+            }
+        }
+    }
+}
+
+/// The direction of the lowest adjacent value, used to move towards the
+/// goals of a dijkstra map.
+///
+/// Only the directions of `D` are considered, which should match the
+/// adjacency the map was built with. Positions that can't reach any goal,
+/// including impassable positions, are ignored.
+///
+/// Returns [None] if no adjacent value is lower than the value at `xy`.
+pub fn downhill<D: Adjacency>(map: &FloatGrid, xy: impl GridPoint) -> Option<Dir8> {
+    steepest::<D>(map, xy.to_ivec2(), |next, best| next < best)
+}
+
+/// The direction of the highest adjacent value, used to move away from the
+/// goals of a dijkstra map.
+///
+/// Only the directions of `D` are considered, which should match the
+/// adjacency the map was built with. Positions that can't reach any goal,
+/// including impassable positions, are ignored.
+///
+/// Returns [None] if no adjacent value is higher than the value at `xy`.
+pub fn uphill<D: Adjacency>(map: &FloatGrid, xy: impl GridPoint) -> Option<Dir8> {
+    steepest::<D>(map, xy.to_ivec2(), |next, best| next > best)
+}
+
+fn steepest<D: Adjacency>(
+    map: &FloatGrid,
+    xy: IVec2,
+    better: impl Fn(f32, f32) -> bool,
+) -> Option<Dir8> {
+    let mut best = map.get_value(xy)?;
+    let mut best_dir = None;
+    for dir in D::directions() {
+        let Some(v) = map.get_value(xy + *dir) else {
+            continue;
+        };
+        if v.is_finite() && better(v, best) {
+            best = v;
+            best_dir = Dir8::from_point(*dir);
+        }
+    }
+    best_dir
+}
+
+/// Combine multiple dijkstra maps into a single map by summing their values
+/// multiplied by their weights.
+///
+/// A negative weight can be used to turn an "approach" map into a "flee" map.
+/// Maps with a weight of zero are ignored. A cell that is unreachable in any
+/// map with a nonzero weight is unreachable in the combined map, and is set to
+/// [f32::INFINITY] regardless of the weights.
+///
+/// All maps must be the same size, and at least one must have a nonzero
+/// weight.
+pub fn combine(maps: &[(&FloatGrid, f32)]) -> FloatGrid {
+    assert!(
+        !maps.is_empty(),
+        "Attempting to combine an empty list of maps"
+    );
+    assert!(
+        maps.iter().any(|(_, weight)| *weight != 0.0),
+        "Attempting to combine dijkstra maps that all have a weight of zero"
+    );
+    let size = maps[0].0.size();
+    let mut combined = FloatGrid::new(size);
+    for (map, weight) in maps {
+        assert_eq!(
+            size,
+            map.size(),
+            "Attempting to combine dijkstra maps of different sizes"
+        );
+        if *weight == 0.0 {
+            continue;
+        }
+        for (out, v) in combined.values_mut().iter_mut().zip(map.values()) {
+            *out = match v.is_finite() {
+                true => *out + v * weight,
+                false => f32::INFINITY,
+            };
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::Dir4;
+
+    #[test]
+    fn multiple_goals() {
+        let walkable = BitGrid::new([11, 1]).with_value(true);
+        let map = from_bit_grid::<Dir4>(&walkable, [[0, 0], [10, 0]]);
+
+        assert_eq!(0.0, map.value([0, 0]));
+        assert_eq!(0.0, map.value([10, 0]));
+        assert_eq!(5.0, map.value([5, 0]));
+        assert_eq!(2.0, map.value([8, 0]));
+    }
+
+    #[test]
+    fn unreachable() {
+        let mut walkable = BitGrid::new([5, 5]).with_value(true);
+        for y in 0..5 {
+            walkable.set_false([2, y]);
+        }
+        let map = from_bit_grid::<Dir8>(&walkable, [[0, 0]]);
+
+        assert_eq!(1.0, map.value([1, 1]));
+        assert_eq!(f32::INFINITY, map.value([2, 2]));
+        assert_eq!(f32::INFINITY, map.value([4, 4]));
+    }
+
+    #[test]
+    fn follow_gradient() {
+        let walkable = BitGrid::new([10, 10]).with_value(true);
+        let map = from_bit_grid::<Dir8>(&walkable, [[0, 0]]);
+
+        let mut p = IVec2::new(6, 9);
+        while let Some(dir) = downhill::<Dir8>(&map, p) {
+            p += IVec2::from(dir);
+        }
+        assert_eq!(IVec2::ZERO, p);
+
+        assert_eq!(None, uphill::<Dir8>(&map, [9, 9]));
+
+        let map = from_bit_grid::<Dir4>(&walkable, [[0, 0]]);
+        assert_eq!(Some(Dir8::Up), uphill::<Dir4>(&map, [3, 3]));
+    }
+
+    #[test]
+    fn corridor_corner() {
+        // An L shaped corridor, where the diagonal past the corner is lower
+        // but can't be reached with a single Dir4 move.
+        let mut walkable = BitGrid::new([3, 3]);
+        for p in [[0, 0], [1, 0], [2, 0], [2, 1], [2, 2]] {
+            walkable.set_true(p);
+        }
+        let map = from_bit_grid::<Dir4>(&walkable, [[0, 0]]);
+        assert_eq!(1.0, map.value([1, 0]));
+        assert_eq!(3.0, map.value([2, 1]));
+
+        assert_eq!(Some(Dir8::Down), downhill::<Dir4>(&map, [2, 1]));
+        assert_eq!(Some(Dir8::Left), downhill::<Dir4>(&map, [2, 0]));
+        assert_eq!(Some(Dir8::Up), uphill::<Dir4>(&map, [2, 1]));
+        assert_eq!(None, uphill::<Dir4>(&map, [2, 2]));
+
+        let mut p = IVec2::new(2, 2);
+        let mut steps = 0;
+        while let Some(dir) = downhill::<Dir4>(&map, p) {
+            p += IVec2::from(dir);
+            assert!(walkable.get(p));
+            steps += 1;
+        }
+        assert_eq!((IVec2::ZERO, 4), (p, steps));
+    }
+
+    #[test]
+    fn combined() {
+        let walkable = BitGrid::new([10, 1]).with_value(true);
+        let a = from_bit_grid::<Dir4>(&walkable, [[0, 0]]);
+        let b = from_bit_grid::<Dir4>(&walkable, [[9, 0]]);
+
+        let map = combine(&[(&a, 1.0), (&b, -0.5)]);
+        assert_eq!(-4.5, map.value([0, 0]));
+        assert_eq!(9.0, map.value([9, 0]));
+    }
+
+    #[test]
+    fn combined_unreachable() {
+        let mut walkable = BitGrid::new([3, 1]).with_value(true);
+        walkable.set_false([1, 0]);
+        let a = from_bit_grid::<Dir4>(&walkable, [[0, 0]]);
+
+        let map = combine(&[(&a, 1.0), (&a, -1.0)]);
+        assert_eq!(0.0, map.value([0, 0]));
+        assert_eq!(f32::INFINITY, map.value([1, 0]));
+        assert_eq!(f32::INFINITY, map.value([2, 0]));
+
+        // A zero weight map doesn't make cells unreachable.
+        let open = from_bit_grid::<Dir4>(&BitGrid::new([3, 1]).with_value(true), [[2, 0]]);
+        let map = combine(&[(&open, 1.0), (&a, 0.0)]);
+        assert_eq!(1.0, map.value([1, 0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn combined_all_zero_weights() {
+        let mut walkable = BitGrid::new([3, 1]).with_value(true);
+        walkable.set_false([1, 0]);
+        let a = from_bit_grid::<Dir4>(&walkable, [[0, 0]]);
+        combine(&[(&a, 0.0), (&a, 0.0)]);
+    }
+}
@@ -22,6 +22,7 @@ pub const DIR_8: &[IVec2] = &[
 
 /// Four orthogonal directions on a 2d grid.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir4 {
     Up,
     Down,
@@ -76,6 +77,7 @@ impl Dir4 {
 
 /// 8 directions on a 2d grid.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir8 {
     Up,
     Down,
@@ -147,3 +149,34 @@ impl From<Dir8> for IVec2 {
         }
     }
 }
+
+/// A set of directions defining which grid points are considered adjacent to
+/// each other. Implemented for [Dir4] and [Dir8] so algorithms can be told
+/// which kind of movement or connectivity to use, ie: `astar::<Dir8>(..)`.
+pub trait Adjacency {
+    /// The offsets to each adjacent grid point.
+    fn directions() -> &'static [IVec2];
+
+    /// The distance between two points when only moving in these directions.
+    fn distance(a: impl GridPoint, b: impl GridPoint) -> usize;
+}
+
+impl Adjacency for Dir4 {
+    fn directions() -> &'static [IVec2] {
+        DIR_4
+    }
+
+    fn distance(a: impl GridPoint, b: impl GridPoint) -> usize {
+        a.taxi_dist(b)
+    }
+}
+
+impl Adjacency for Dir8 {
+    fn directions() -> &'static [IVec2] {
+        DIR_8
+    }
+
+    fn distance(a: impl GridPoint, b: impl GridPoint) -> usize {
+        a.king_dist(b)
+    }
+}
@@ -0,0 +1,251 @@
+//! Distance transforms, where each value is the distance from that position
+//! to the nearest set bit of a [BitGrid].
+//!
+//! Useful for clearance maps, signed distance fields, and anything else that
+//! needs to know how far away the nearest feature is. If no bits are set every
+//! value is [f32::INFINITY].
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{distance_transform::{self, Metric}, BitGrid};
+//!
+//! let mut walls = BitGrid::new([10, 10]);
+//! walls.set_true([0, 0]);
+//!
+//! let map = distance_transform::build(&walls, Metric::Euclidean);
+//! assert_eq!(5.0, map.value([3, 4]));
+//!
+//! let map = distance_transform::build(&walls, Metric::Taxi);
+//! assert_eq!(7.0, map.value([3, 4]));
+//! ```
+
+use crate::{BitGrid, FloatGrid, SizedGrid};
+
+/// The distance metric used by a distance transform.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Metric {
+    /// Exact straight line distance.
+    #[default]
+    Euclidean,
+    /// Four-way distance, matching [GridPoint::taxi_dist](crate::GridPoint::taxi_dist).
+    Taxi,
+    /// Eight-way distance, matching [GridPoint::king_dist](crate::GridPoint::king_dist).
+    King,
+}
+
+/// Calculate the distance from each position to the nearest set bit.
+pub fn build(grid: &BitGrid, metric: Metric) -> FloatGrid {
+    let mut out = FloatGrid::new(grid.size());
+    build_into(grid, metric, &mut out);
+    out
+}
+
+/// Calculate the distance from each position to the nearest set bit, writing
+/// the result into an existing grid of the same size.
+pub fn build_into(grid: &BitGrid, metric: Metric, out: &mut FloatGrid) {
+    assert_eq!(
+        grid.size(),
+        out.size(),
+        "Attempting to build a distance transform into a grid of a different size"
+    );
+    for (v, set) in out.values_mut().iter_mut().zip(grid.iter()) {
+        *v = if set { 0.0 } else { f32::INFINITY };
+    }
+    match metric {
+        Metric::Euclidean => euclidean(out),
+        Metric::Taxi => chamfer(out, false),
+        Metric::King => chamfer(out, true),
+    }
+}
+
+/// Calculate the signed distance from each position to the edge of the set
+/// bits.
+///
+/// Unset positions are the distance to the nearest set bit, set positions are
+/// the negative distance to the nearest unset bit.
+pub fn signed(grid: &BitGrid, metric: Metric) -> FloatGrid {
+    let mut out = build(grid, metric);
+    let inside = build(&!grid, metric);
+    for ((v, inside), set) in out
+        .values_mut()
+        .iter_mut()
+        .zip(inside.values())
+        .zip(grid.iter())
+    {
+        if set {
+            *v = -inside;
+        }
+    }
+    out
+}
+
+/// Two pass chamfer transform, exact for taxicab and king's distance.
+fn chamfer(grid: &mut FloatGrid, diagonals: bool) {
+    let [w, h] = grid.size().to_array().map(|v| v as usize);
+    let d = grid.values_mut();
+    for y in 0..h {
+        for x in 0..w {
+            let mut v = d[y * w + x];
+            if x > 0 {
+                v = v.min(d[y * w + x - 1] + 1.0);
+            }
+            if y > 0 {
+                v = v.min(d[(y - 1) * w + x] + 1.0);
+                if diagonals && x > 0 {
+                    v = v.min(d[(y - 1) * w + x - 1] + 1.0);
+                }
+                if diagonals && x + 1 < w {
+                    v = v.min(d[(y - 1) * w + x + 1] + 1.0);
+                }
+            }
+            d[y * w + x] = v;
+        }
+    }
+    for y in (0..h).rev() {
+        for x in (0..w).rev() {
+            let mut v = d[y * w + x];
+            if x + 1 < w {
+                v = v.min(d[y * w + x + 1] + 1.0);
+            }
+            if y + 1 < h {
+                v = v.min(d[(y + 1) * w + x] + 1.0);
+                if diagonals && x > 0 {
+                    v = v.min(d[(y + 1) * w + x - 1] + 1.0);
+                }
+                if diagonals && x + 1 < w {
+                    v = v.min(d[(y + 1) * w + x + 1] + 1.0);
+                }
+            }
+            d[y * w + x] = v;
+        }
+    }
+}
+
+/// Exact euclidean distance transform from
+/// [Felzenszwalb and Huttenlocher](https://cs.brown.edu/people/pfelzens/papers/dt-final.pdf),
+/// applied to each column and then each row.
+fn euclidean(grid: &mut FloatGrid) {
+    let [w, h] = grid.size().to_array().map(|v| v as usize);
+    let d = grid.values_mut();
+    let mut f = Vec::with_capacity(w.max(h));
+    let mut out = vec![0.0; w.max(h)];
+    let mut envelope = Envelope::default();
+
+    for x in 0..w {
+        f.clear();
+        f.extend((0..h).map(|y| d[y * w + x]));
+        envelope.transform(&f, &mut out[..h]);
+        for y in 0..h {
+            d[y * w + x] = out[y];
+        }
+    }
+    for row in d.chunks_mut(w.max(1)) {
+        f.clear();
+        f.extend_from_slice(row);
+        envelope.transform(&f, &mut out[..w]);
+        row.copy_from_slice(&out[..w]);
+    }
+    d.iter_mut().for_each(|v| *v = v.sqrt());
+}
+
+/// The lower envelope of parabolas used by the 1d squared euclidean distance
+/// transform.
+#[derive(Default)]
+struct Envelope {
+    /// Position of each parabola's vertex.
+    vertices: Vec<usize>,
+    /// Left edge of the range where each parabola is the lowest.
+    starts: Vec<f32>,
+}
+
+impl Envelope {
+    /// Transform the squared distances in `f` into `out`. Infinite values are
+    /// skipped when building the envelope.
+    fn transform(&mut self, f: &[f32], out: &mut [f32]) {
+        self.vertices.clear();
+        self.starts.clear();
+        let height = |q: usize| f[q] + (q * q) as f32;
+        for q in (0..f.len()).filter(|q| f[*q].is_finite()) {
+            let start = loop {
+                let Some(&p) = self.vertices.last() else {
+                    break f32::NEG_INFINITY;
+                };
+                let s = (height(q) - height(p)) / (2 * (q - p)) as f32;
+                if s <= *self.starts.last().unwrap() {
+                    self.vertices.pop();
+                    self.starts.pop();
+                } else {
+                    break s;
+                }
+            };
+            self.vertices.push(q);
+            self.starts.push(start);
+        }
+
+        if self.vertices.is_empty() {
+            out.fill(f32::INFINITY);
+            return;
+        }
+        let mut k = 0;
+        for (q, v) in out.iter_mut().enumerate() {
+            while k + 1 < self.vertices.len() && self.starts[k + 1] < q as f32 {
+                k += 1;
+            }
+            let p = self.vertices[k];
+            let dist = q as f32 - p as f32;
+            *v = dist * dist + f[p];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bit_grid::noise, GridPoint};
+
+    #[test]
+    fn matches_brute_force() {
+        let grid = noise([23, 17], 7, 16);
+        let set: Vec<_> = grid.iter_xy().filter(|(_, v)| *v).map(|(p, _)| p).collect();
+        assert!(!set.is_empty());
+
+        let euclidean = build(&grid, Metric::Euclidean);
+        let taxi = build(&grid, Metric::Taxi);
+        let king = build(&grid, Metric::King);
+        for p in grid.iter_grid_points() {
+            let nearest = |dist: &dyn Fn(&glam::IVec2) -> f32| {
+                set.iter().map(dist).fold(f32::INFINITY, f32::min)
+            };
+            let expected = nearest(&|s| s.as_vec2().distance(p.as_vec2()));
+            assert!((expected - euclidean.value(p)).abs() < 1e-4, "{p}");
+            assert_eq!(nearest(&|s| s.taxi_dist(p) as f32), taxi.value(p));
+            assert_eq!(nearest(&|s| s.king_dist(p) as f32), king.value(p));
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let grid = BitGrid::new([4, 3]);
+        for metric in [Metric::Euclidean, Metric::Taxi, Metric::King] {
+            let map = build(&grid, metric);
+            assert!(map.values().iter().all(|v| *v == f32::INFINITY));
+        }
+    }
+
+    #[test]
+    fn signed_distance() {
+        let mut grid = BitGrid::new([7, 7]);
+        for x in 1..6 {
+            for y in 1..6 {
+                grid.set_true([x, y]);
+            }
+        }
+        let map = signed(&grid, Metric::Taxi);
+        assert_eq!(1.0, map.value([0, 3]));
+        assert_eq!(-1.0, map.value([1, 3]));
+        assert_eq!(-3.0, map.value([3, 3]));
+        assert_eq!(2.0, map.value([0, 0]));
+    }
+}
@@ -1,19 +1,53 @@
 //! A rectangular grid of float values with utility functions for performing operations
 //! across the grid.
 
+mod filter;
+mod ops;
+mod sample;
+
 use std::ops::{Index, IndexMut};
 
 use glam::{IVec2, UVec2};
 
-use crate::{GridPoint, GridRect, GridSize, SizedGrid};
+use crate::{grid::clip_blit, BitGrid, GridPoint, GridRect, GridSize, SizedGrid};
+
+pub use sample::{EdgeMode, Interpolation};
 
 /// A rectangular grid of floating point values.
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "FloatGridData"))]
 pub struct FloatGrid {
     data: Vec<f32>,
     size: UVec2,
 }
 
+/// Unvalidated grid data, used to check the size of a [FloatGrid] when it's
+/// deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FloatGridData {
+    data: Vec<f32>,
+    size: UVec2,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FloatGridData> for FloatGrid {
+    type Error = String;
+
+    fn try_from(grid: FloatGridData) -> Result<Self, Self::Error> {
+        let FloatGridData { data, size } = grid;
+        if data.len() != size.x as usize * size.y as usize {
+            return Err(format!(
+                "Grid data length {} doesn't match grid size {}",
+                data.len(),
+                size
+            ));
+        }
+        Ok(Self { data, size })
+    }
+}
+
 impl SizedGrid for FloatGrid {
     fn size(&self) -> glam::UVec2 {
         self.size
@@ -98,6 +132,69 @@ impl FloatGrid {
             .flat_map(move |tiles| tiles[rect.left() as usize..=rect.right() as usize].iter_mut())
     }
 
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`.
+    ///
+    /// The area is clipped to the bounds of both grids.
+    pub fn blit(&mut self, src: &FloatGrid, src_rect: GridRect, dest_pos: impl GridPoint) {
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        let w = rect.width();
+        for y in rect.bottom()..=rect.top() {
+            let s = src.transform_lti([rect.left(), y]);
+            let d = self.transform_lti(IVec2::new(rect.left(), y) + offset);
+            self.data[d..d + w].copy_from_slice(&src.data[s..s + w]);
+        }
+    }
+
+    /// Combine a rectangular area of another grid with this one, with the
+    /// bottom left of the area placed at `dest_pos`.
+    ///
+    /// `blend` is called with the existing value and the value from `src`. The
+    /// area is clipped to the bounds of both grids.
+    pub fn blit_with(
+        &mut self,
+        src: &FloatGrid,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        blend: impl Fn(f32, f32) -> f32,
+    ) {
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points() {
+            let v = &mut self[p + offset];
+            *v = blend(*v, src[p]);
+        }
+    }
+
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`. Only values where the bit at the
+    /// same position in `mask` is set are copied.
+    ///
+    /// The mask must be the same size as `src`. The area is clipped to the
+    /// bounds of both grids.
+    pub fn blit_masked(
+        &mut self,
+        src: &FloatGrid,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        mask: &BitGrid,
+    ) {
+        assert_eq!(
+            src.size,
+            mask.size(),
+            "Attempting to blit with a mask of a different size than the source grid"
+        );
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points().filter(|p| mask.get(*p)) {
+            self[p + offset] = src[p];
+        }
+    }
+
     /// Reset all values in the [FloatGrid] to 0.
     pub fn clear(&mut self) {
         self.data.fill(0.0);
@@ -143,3 +240,45 @@ impl IndexMut<usize> for FloatGrid {
         &mut self.data[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blit() {
+        let mut grid = FloatGrid::new([3, 3]);
+        let mut src = FloatGrid::new([2, 2]);
+        src.set_all(1.0);
+        src.set_value([1, 1], 2.0);
+
+        grid.blit(&src, GridRect::new([0, 0], [2, 2]), [2, 2]);
+        assert_eq!(1.0, grid.values().iter().sum::<f32>());
+        assert_eq!(1.0, grid.value([2, 2]));
+
+        grid.blit_with(&src, GridRect::new([0, 0], [2, 2]), [1, 1], f32::max);
+        assert_eq!(1.0, grid.value([1, 1]));
+        assert_eq!(2.0, grid.value([2, 2]));
+
+        let mut mask = BitGrid::new([2, 2]);
+        mask.set_true([0, 1]);
+        grid.blit_masked(&src, GridRect::new([0, 0], [2, 2]), [-1, -1], &mask);
+        assert_eq!(0.0, grid.value([0, 0]));
+        grid.blit_masked(&src, GridRect::new([0, 0], [2, 2]), [0, -1], &mask);
+        assert_eq!(1.0, grid.value([0, 0]));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = FloatGrid::new([3, 2]);
+        grid.set_value([1, 1], 0.5);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: FloatGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(grid, loaded);
+
+        let wrong_size = r#"{"data":[1.0,2.0,3.0],"size":[1,2]}"#;
+        assert!(serde_json::from_str::<FloatGrid>(wrong_size).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Convolution and image style filters for a [FloatGrid].
+
+use glam::IVec2;
+
+use crate::{FloatGrid, SizedGrid};
+
+use super::EdgeMode;
+
+impl FloatGrid {
+    /// Convolve the grid with a kernel, using the edge mode for positions
+    /// outside the grid.
+    ///
+    /// Each value of the result is the sum of the surrounding values
+    /// multiplied by the overlapping kernel values, with the center of the
+    /// kernel at `kernel.size() / 2`. The kernel is not flipped.
+    pub fn convolve(&self, kernel: &FloatGrid, edge: EdgeMode) -> FloatGrid {
+        let center = kernel.size().as_ivec2() / 2;
+        let taps: Vec<_> = kernel
+            .iter_xy()
+            .filter(|(_, k)| *k != 0.0)
+            .map(|(p, k)| (p - center, k))
+            .collect();
+        let mut out = FloatGrid::new(self.size());
+        for (p, v) in out.iter_xy_muy() {
+            *v = taps
+                .iter()
+                .map(|(offset, k)| k * self.edge_value(p + *offset, edge))
+                .sum();
+        }
+        out
+    }
+
+    /// Convolve the grid with a separable kernel, first along each row then
+    /// along each column.
+    ///
+    /// The center of each kernel is at `kernel.len() / 2`.
+    pub fn convolve_separable(
+        &self,
+        horizontal: &[f32],
+        vertical: &[f32],
+        edge: EdgeMode,
+    ) -> FloatGrid {
+        let pass = |grid: &FloatGrid, kernel: &[f32], axis: IVec2| {
+            let center = (kernel.len() / 2) as i32;
+            let mut out = FloatGrid::new(grid.size());
+            for (p, v) in out.iter_xy_muy() {
+                *v = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(i, k)| k * grid.edge_value(p + axis * (i as i32 - center), edge))
+                    .sum();
+            }
+            out
+        };
+        let rows = pass(self, horizontal, IVec2::X);
+        pass(&rows, vertical, IVec2::Y)
+    }
+
+    /// Blur the grid with a gaussian kernel of the given standard deviation.
+    pub fn gaussian_blur(&self, sigma: f32, edge: EdgeMode) -> FloatGrid {
+        if sigma <= 0.0 {
+            return self.clone();
+        }
+        let radius = (sigma * 3.0).ceil() as i32;
+        let mut kernel: Vec<_> = (-radius..=radius)
+            .map(|x| (-((x * x) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let sum: f32 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|k| *k /= sum);
+        self.convolve_separable(&kernel, &kernel, edge)
+    }
+
+    /// Blur the grid by averaging each value with the values within `radius`
+    /// of it on each axis.
+    pub fn box_blur(&self, radius: usize, edge: EdgeMode) -> FloatGrid {
+        let len = radius * 2 + 1;
+        let kernel = vec![1.0 / len as f32; len];
+        self.convolve_separable(&kernel, &kernel, edge)
+    }
+
+    /// Calculate the horizontal and vertical gradient of the grid with the
+    /// Sobel operator.
+    ///
+    /// Gradients are positive when values increase to the right and upwards.
+    pub fn sobel(&self, edge: EdgeMode) -> (FloatGrid, FloatGrid) {
+        let smooth = [1.0, 2.0, 1.0];
+        let derive = [-1.0, 0.0, 1.0];
+        let x = self.convolve_separable(&derive, &smooth, edge);
+        let y = self.convolve_separable(&smooth, &derive, edge);
+        (x, y)
+    }
+
+    /// Apply the four-way Laplacian operator to the grid, the sum of the
+    /// differences between each value and it's orthogonal neighbors.
+    pub fn laplacian(&self, edge: EdgeMode) -> FloatGrid {
+        let mut out = FloatGrid::new(self.size());
+        for (p, v) in out.iter_xy_muy() {
+            let neighbors: f32 = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                .iter()
+                .map(|d| self.edge_value(p + *d, edge))
+                .sum();
+            *v = neighbors - 4.0 * self.edge_value(p, edge);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse() -> FloatGrid {
+        let mut grid = FloatGrid::new([7, 7]);
+        grid.set_value([3, 3], 1.0);
+        grid
+    }
+
+    #[test]
+    fn convolve() {
+        let mut kernel = FloatGrid::new([3, 3]);
+        kernel.set_value([2, 1], 1.0);
+        let shifted = impulse().convolve(&kernel, EdgeMode::Zero);
+        // The value at [2,3] reads from it's right neighbor.
+        assert_eq!(1.0, shifted.value([2, 3]));
+        assert_eq!(1.0, shifted.values().iter().sum::<f32>());
+
+        let blurred = impulse().box_blur(1, EdgeMode::Zero);
+        let mut box_kernel = FloatGrid::new([3, 3]);
+        box_kernel.set_all(1.0 / 9.0);
+        let expected = impulse().convolve(&box_kernel, EdgeMode::Zero);
+        for (a, b) in blurred.values().iter().zip(expected.values()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn blur() {
+        let blurred = impulse().gaussian_blur(1.0, EdgeMode::Zero);
+        let sum: f32 = blurred.values().iter().sum();
+        assert!((sum - 1.0).abs() < 0.01);
+        assert!(blurred.value([3, 3]) > blurred.value([3, 4]));
+        assert_eq!(blurred.value([2, 3]), blurred.value([4, 3]));
+
+        let mut ones = FloatGrid::new([4, 4]);
+        ones.set_all(1.0);
+        let blurred = ones.box_blur(2, EdgeMode::Clamp);
+        assert!(blurred.values().iter().all(|v| (v - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn gradients() {
+        let mut ramp = FloatGrid::new([5, 5]);
+        for (p, v) in ramp.iter_xy_muy() {
+            *v = p.y as f32 * 2.0;
+        }
+        let (x, y) = ramp.sobel(EdgeMode::Clamp);
+        assert_eq!(0.0, x.value([2, 2]));
+        assert_eq!(16.0, y.value([2, 2]));
+
+        let lap = impulse().laplacian(EdgeMode::Zero);
+        assert_eq!(-4.0, lap.value([3, 3]));
+        assert_eq!(1.0, lap.value([3, 4]));
+        assert_eq!(0.0, ramp.laplacian(EdgeMode::Wrap).value([2, 2]));
+    }
+}
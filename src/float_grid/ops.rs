@@ -0,0 +1,255 @@
+//! Arithmetic operators and reductions for a [FloatGrid].
+
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+use glam::IVec2;
+
+use crate::{FloatGrid, SizedGrid};
+
+impl FloatGrid {
+    /// Combine each value with the corresponding value in another grid of the
+    /// same size.
+    pub fn zip_apply(&mut self, other: &FloatGrid, operation: impl Fn(f32, f32) -> f32) {
+        assert_eq!(
+            self.size(),
+            other.size(),
+            "Attempting to combine FloatGrids of different sizes"
+        );
+        for (a, b) in self.values_mut().iter_mut().zip(other.values()) {
+            *a = operation(*a, *b);
+        }
+    }
+
+    /// The smallest value in the grid, ignoring NaN values.
+    ///
+    /// Returns [f32::INFINITY] if the grid is empty.
+    pub fn min(&self) -> f32 {
+        self.values().iter().copied().fold(f32::INFINITY, f32::min)
+    }
+
+    /// The largest value in the grid, ignoring NaN values.
+    ///
+    /// Returns [f32::NEG_INFINITY] if the grid is empty.
+    pub fn max(&self) -> f32 {
+        self.values()
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// The sum of all values in the grid.
+    pub fn sum(&self) -> f32 {
+        self.values().iter().sum()
+    }
+
+    /// The average of all values in the grid.
+    ///
+    /// Returns NaN if the grid is empty.
+    pub fn mean(&self) -> f32 {
+        self.sum() / self.tile_count() as f32
+    }
+
+    /// The position of the smallest value in the grid, ignoring NaN values.
+    ///
+    /// Returns [None] if the grid is empty or only contains NaN values.
+    pub fn argmin(&self) -> Option<IVec2> {
+        self.iter_xy()
+            .filter(|(_, v)| !v.is_nan())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(p, _)| p)
+    }
+
+    /// The position of the largest value in the grid, ignoring NaN values.
+    ///
+    /// Returns [None] if the grid is empty or only contains NaN values.
+    pub fn argmax(&self) -> Option<IVec2> {
+        self.iter_xy()
+            .filter(|(_, v)| !v.is_nan())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(p, _)| p)
+    }
+
+    /// Linearly remap all finite values so the smallest becomes `min` and the
+    /// largest becomes `max`.
+    ///
+    /// Infinite and NaN values, such as unreachable positions in a dijkstra
+    /// map, are left unchanged. If every finite value is the same they are all
+    /// set to `min`.
+    pub fn normalize(&mut self, min: f32, max: f32) {
+        let finite = self.values().iter().copied().filter(|v| v.is_finite());
+        let (lo, hi) = finite.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        let range = hi - lo;
+        self.apply_operation(|v| match v.is_finite() {
+            false => v,
+            true if range > 0.0 => min + (v - lo) / range * (max - min),
+            true => min,
+        });
+    }
+
+    /// Clamp all values to the given range. NaN values are left unchanged.
+    ///
+    /// Panics if `min` is greater than `max`, or either is NaN.
+    pub fn clamp(&mut self, min: f32, max: f32) {
+        self.apply_operation(|v| v.clamp(min, max));
+    }
+}
+
+macro_rules! impl_float_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident, $sym:tt) => {
+        impl $assign_op<&FloatGrid> for FloatGrid {
+            fn $assign_fn(&mut self, rhs: &FloatGrid) {
+                self.zip_apply(rhs, |a, b| a $sym b);
+            }
+        }
+
+        impl $assign_op for FloatGrid {
+            fn $assign_fn(&mut self, rhs: FloatGrid) {
+                self.zip_apply(&rhs, |a, b| a $sym b);
+            }
+        }
+
+        impl $assign_op<f32> for FloatGrid {
+            fn $assign_fn(&mut self, rhs: f32) {
+                self.apply_operation(|v| v $sym rhs);
+            }
+        }
+
+        impl $op<&FloatGrid> for &FloatGrid {
+            type Output = FloatGrid;
+
+            fn $fn(self, rhs: &FloatGrid) -> FloatGrid {
+                let mut grid = self.clone();
+                grid.zip_apply(rhs, |a, b| a $sym b);
+                grid
+            }
+        }
+
+        impl $op<&FloatGrid> for FloatGrid {
+            type Output = FloatGrid;
+
+            fn $fn(mut self, rhs: &FloatGrid) -> FloatGrid {
+                self.zip_apply(rhs, |a, b| a $sym b);
+                self
+            }
+        }
+
+        impl $op for FloatGrid {
+            type Output = FloatGrid;
+
+            fn $fn(mut self, rhs: FloatGrid) -> FloatGrid {
+                self.zip_apply(&rhs, |a, b| a $sym b);
+                self
+            }
+        }
+
+        impl $op<f32> for &FloatGrid {
+            type Output = FloatGrid;
+
+            fn $fn(self, rhs: f32) -> FloatGrid {
+                let mut grid = self.clone();
+                grid.apply_operation(|v| v $sym rhs);
+                grid
+            }
+        }
+
+        impl $op<f32> for FloatGrid {
+            type Output = FloatGrid;
+
+            fn $fn(mut self, rhs: f32) -> FloatGrid {
+                self.apply_operation(|v| v $sym rhs);
+                self
+            }
+        }
+    };
+}
+
+impl_float_op!(Add, add, AddAssign, add_assign, +);
+impl_float_op!(Sub, sub, SubAssign, sub_assign, -);
+impl_float_op!(Mul, mul, MulAssign, mul_assign, *);
+impl_float_op!(Div, div, DivAssign, div_assign, /);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(values: [f32; 4]) -> FloatGrid {
+        let mut grid = FloatGrid::new([2, 2]);
+        grid.values_mut().copy_from_slice(&values);
+        grid
+    }
+
+    #[test]
+    fn operators() {
+        let a = grid([1.0, 2.0, 3.0, 4.0]);
+        let b = grid([4.0, 3.0, 2.0, 1.0]);
+
+        assert_eq!(grid([5.0; 4]), &a + &b);
+        assert_eq!(grid([-3.0, -1.0, 1.0, 3.0]), &a - &b);
+        assert_eq!(grid([4.0, 6.0, 6.0, 4.0]), a.clone() * b.clone());
+        assert_eq!(grid([0.5, 1.0, 1.5, 2.0]), &a / 2.0);
+
+        let mut c = a.clone();
+        c += &b;
+        c *= 2.0;
+        c -= 1.0;
+        assert_eq!(grid([9.0; 4]), c);
+
+        let mut d = a.clone();
+        d.zip_apply(&b, f32::max);
+        assert_eq!(grid([4.0, 3.0, 3.0, 4.0]), d);
+    }
+
+    #[test]
+    #[should_panic]
+    fn size_mismatch() {
+        let _ = &grid([0.0; 4]) + &FloatGrid::new([3, 3]);
+    }
+
+    #[test]
+    fn reductions() {
+        let mut g = grid([3.0, -1.0, f32::NAN, 7.0]);
+        assert_eq!(-1.0, g.min());
+        assert_eq!(7.0, g.max());
+        assert_eq!(Some(IVec2::new(1, 0)), g.argmin());
+        assert_eq!(Some(IVec2::new(1, 1)), g.argmax());
+
+        g.values_mut()[2] = 3.0;
+        assert_eq!(12.0, g.sum());
+        assert_eq!(3.0, g.mean());
+
+        g.normalize(0.0, 1.0);
+        assert_eq!(grid([0.5, 0.0, 0.5, 1.0]), g);
+        g.clamp(0.25, 0.75);
+        assert_eq!(grid([0.5, 0.25, 0.5, 0.75]), g);
+
+        assert_eq!(None, FloatGrid::new([0, 0]).argmax());
+    }
+
+    #[test]
+    fn empty_mean() {
+        assert!(FloatGrid::new([0, 0]).mean().is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn clamp_inverted_range() {
+        grid([0.0; 4]).clamp(1.0, 0.0);
+    }
+
+    #[test]
+    fn normalize_non_finite() {
+        let mut g = grid([0.0, 5.0, f32::INFINITY, 10.0]);
+        g.normalize(0.0, 1.0);
+        assert_eq!(grid([0.0, 0.5, f32::INFINITY, 1.0]), g);
+
+        let mut g = grid([2.0, f32::NEG_INFINITY, 2.0, f32::INFINITY]);
+        g.normalize(-1.0, 1.0);
+        assert_eq!(grid([-1.0, f32::NEG_INFINITY, -1.0, f32::INFINITY]), g);
+
+        let mut g = grid([f32::INFINITY; 4]);
+        g.normalize(0.0, 1.0);
+        assert_eq!(grid([f32::INFINITY; 4]), g);
+    }
+}
@@ -0,0 +1,172 @@
+//! Sampling a [FloatGrid] at fractional positions.
+
+use glam::{IVec2, Vec2};
+
+use crate::{FloatGrid, GridSize, SizedGrid};
+
+/// How values are interpolated when sampling between grid positions.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    /// Use the value of the nearest grid position.
+    Nearest,
+    /// Linearly interpolate between the four surrounding grid positions.
+    #[default]
+    Bilinear,
+    /// Cubic (Catmull-Rom) interpolation between the sixteen surrounding grid
+    /// positions.
+    Bicubic,
+}
+
+/// How positions outside the grid are treated when reading values.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeMode {
+    /// Use the value of the nearest position on the edge of the grid.
+    #[default]
+    Clamp,
+    /// Wrap around to the opposite side of the grid.
+    Wrap,
+    /// Positions outside the grid have a value of zero.
+    Zero,
+}
+
+impl FloatGrid {
+    /// Read the value at a position, using the edge mode for positions
+    /// outside the grid.
+    pub fn edge_value(&self, xy: IVec2, edge: EdgeMode) -> f32 {
+        if self.tile_count() == 0 {
+            return 0.0;
+        }
+        let size = self.size().as_ivec2();
+        let p = match edge {
+            EdgeMode::Clamp => xy.clamp(IVec2::ZERO, size - 1),
+            EdgeMode::Wrap => xy.rem_euclid(size),
+            EdgeMode::Zero if !self.in_bounds(xy) => return 0.0,
+            EdgeMode::Zero => xy,
+        };
+        self.value(p)
+    }
+
+    /// Sample the grid at a fractional position, where each grid value lies
+    /// at it's integer position.
+    pub fn sample(&self, pos: Vec2, interpolation: Interpolation, edge: EdgeMode) -> f32 {
+        match interpolation {
+            Interpolation::Nearest => self.edge_value(pos.round().as_ivec2(), edge),
+            Interpolation::Bilinear => {
+                let base = pos.floor();
+                let t = pos - base;
+                let p = base.as_ivec2();
+                let v = |x, y| self.edge_value(p + IVec2::new(x, y), edge);
+                let bottom = lerp(v(0, 0), v(1, 0), t.x);
+                let top = lerp(v(0, 1), v(1, 1), t.x);
+                lerp(bottom, top, t.y)
+            }
+            Interpolation::Bicubic => {
+                let base = pos.floor();
+                let t = pos - base;
+                let p = base.as_ivec2();
+                let row = |y| {
+                    let v = |x| self.edge_value(p + IVec2::new(x, y), edge);
+                    cubic(v(-1), v(0), v(1), v(2), t.x)
+                };
+                cubic(row(-1), row(0), row(1), row(2), t.y)
+            }
+        }
+    }
+
+    /// Create a new grid of the given size by sampling this grid.
+    ///
+    /// The grids are aligned at their edges, so each value in the new grid
+    /// is sampled from the same relative position in this one.
+    pub fn resampled(
+        &self,
+        size: impl GridSize,
+        interpolation: Interpolation,
+        edge: EdgeMode,
+    ) -> FloatGrid {
+        let mut out = FloatGrid::new(size);
+        let scale = self.size().as_vec2() / out.size().as_vec2();
+        for (p, v) in out.iter_xy_muy() {
+            let pos = (p.as_vec2() + 0.5) * scale - 0.5;
+            *v = self.sample(pos, interpolation, edge);
+        }
+        out
+    }
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Catmull-Rom interpolation between `b` and `c`.
+#[inline]
+fn cubic(a: f32, b: f32, c: f32, d: f32, t: f32) -> f32 {
+    b + 0.5 * t * (c - a + t * (2.0 * a - 5.0 * b + 4.0 * c - d + t * (3.0 * (b - c) + d - a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp() -> FloatGrid {
+        let mut grid = FloatGrid::new([4, 3]);
+        for (p, v) in grid.iter_xy_muy() {
+            *v = p.x as f32 + 10.0 * p.y as f32;
+        }
+        grid
+    }
+
+    #[test]
+    fn edges() {
+        let grid = ramp();
+        assert_eq!(23.0, grid.edge_value(IVec2::new(5, 2), EdgeMode::Clamp));
+        assert_eq!(21.0, grid.edge_value(IVec2::new(5, 2), EdgeMode::Wrap));
+        assert_eq!(23.0, grid.edge_value(IVec2::new(-1, -1), EdgeMode::Wrap));
+        assert_eq!(0.0, grid.edge_value(IVec2::new(5, 2), EdgeMode::Zero));
+    }
+
+    #[test]
+    fn interpolate() {
+        let grid = ramp();
+        let pos = Vec2::new(1.25, 0.5);
+        assert_eq!(
+            11.0,
+            grid.sample(pos, Interpolation::Nearest, EdgeMode::Clamp)
+        );
+        assert_eq!(
+            6.25,
+            grid.sample(pos, Interpolation::Bilinear, EdgeMode::Clamp)
+        );
+        // Cubic interpolation is exact for linear data away from the edges.
+        let pos = Vec2::new(1.5, 1.0);
+        assert!((grid.sample(pos, Interpolation::Bicubic, EdgeMode::Clamp) - 11.5).abs() < 1e-5);
+        for interpolation in [
+            Interpolation::Nearest,
+            Interpolation::Bilinear,
+            Interpolation::Bicubic,
+        ] {
+            assert_eq!(
+                12.0,
+                grid.sample(Vec2::new(2.0, 1.0), interpolation, EdgeMode::Clamp)
+            );
+        }
+    }
+
+    #[test]
+    fn resample() {
+        let grid = ramp();
+        let same = grid.resampled([4, 3], Interpolation::Bicubic, EdgeMode::Clamp);
+        assert_eq!(grid, same);
+
+        let up = grid.resampled([8, 6], Interpolation::Bilinear, EdgeMode::Clamp);
+        assert_eq!([8, 6], up.size().to_array());
+        assert_eq!(0.0, up.value([0, 0]));
+        assert_eq!(0.25, up.value([1, 0]));
+        assert_eq!(23.0, up.value([7, 5]));
+
+        let down = grid.resampled([2, 1], Interpolation::Bilinear, EdgeMode::Clamp);
+        assert_eq!(10.5, down.value([0, 0]));
+    }
+}
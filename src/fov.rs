@@ -0,0 +1,295 @@
+//! Field of view calculations on a 2d grid using
+//! [symmetric shadowcasting](https://www.albertford.com/shadowcasting/).
+//!
+//! Visible positions are written into a [BitGrid]. Positions outside the
+//! bounds of that grid are treated as opaque.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{fov, BitGrid};
+//!
+//! let mut opaque = BitGrid::new([11, 11]);
+//! opaque.set_true([7, 5]);
+//!
+//! let mut visible = BitGrid::new([11, 11]);
+//! fov::shadowcast(&opaque, [5, 5], 5, &mut visible);
+//!
+//! assert!(visible.get([5, 8]));
+//! assert!(visible.get([7, 5]));
+//! assert!(!visible.get([8, 5]));
+//! ```
+
+use std::f32::consts::PI;
+
+use glam::IVec2;
+
+use crate::{geometry::GridCone, BitGrid, GridPoint, SizedGrid};
+
+/// Calculate the positions visible from `origin` within `radius`, where set
+/// bits in `opaque` block vision.
+///
+/// Visible positions are set to true in `visible`, other positions are left
+/// unchanged. Opaque positions are visible if they are in view.
+pub fn shadowcast(opaque: &BitGrid, origin: impl GridPoint, radius: usize, visible: &mut BitGrid) {
+    shadowcast_with(origin, radius, visible, |p| {
+        !opaque.in_bounds(p) || opaque.get(p)
+    });
+}
+
+/// Calculate the positions visible from `origin` within `radius`, where
+/// `is_opaque` returns true for positions that block vision.
+///
+/// Visible positions are set to true in `visible`, other positions are left
+/// unchanged. Opaque positions are visible if they are in view.
+pub fn shadowcast_with(
+    origin: impl GridPoint,
+    radius: usize,
+    visible: &mut BitGrid,
+    is_opaque: impl Fn(IVec2) -> bool,
+) {
+    Shadowcast {
+        origin: origin.to_ivec2(),
+        radius: radius as i32,
+        visible,
+        is_opaque,
+        in_view: |_| true,
+    }
+    .compute();
+}
+
+/// Calculate the positions visible from the position of a [GridCone] within
+/// the cone's arc and range, where set bits in `opaque` block vision.
+///
+/// Visible positions are set to true in `visible`, other positions are left
+/// unchanged. Opaque positions are visible if they are in view.
+pub fn shadowcast_cone(opaque: &BitGrid, cone: GridCone, visible: &mut BitGrid) {
+    shadowcast_cone_with(cone, visible, |p| !opaque.in_bounds(p) || opaque.get(p));
+}
+
+/// Calculate the positions visible from the position of a [GridCone] within
+/// the cone's arc and range, where `is_opaque` returns true for positions that
+/// block vision.
+///
+/// Visible positions are set to true in `visible`, other positions are left
+/// unchanged. Opaque positions are visible if they are in view.
+pub fn shadowcast_cone_with(
+    cone: GridCone,
+    visible: &mut BitGrid,
+    is_opaque: impl Fn(IVec2) -> bool,
+) {
+    let half_arc = cone.angle_arc_rad / 2.0;
+    Shadowcast {
+        origin: cone.pos,
+        radius: cone.range as i32,
+        visible,
+        is_opaque,
+        in_view: |d: IVec2| {
+            let angle = (d.y as f32).atan2(d.x as f32);
+            let diff = (angle - cone.angle_dir_rad + PI).rem_euclid(2.0 * PI) - PI;
+            diff.abs() <= half_arc
+        },
+    }
+    .compute();
+}
+
+struct Shadowcast<'a, O, V> {
+    origin: IVec2,
+    radius: i32,
+    visible: &'a mut BitGrid,
+    is_opaque: O,
+    /// Filter for positions relative to the origin.
+    in_view: V,
+}
+
+impl<O: Fn(IVec2) -> bool, V: Fn(IVec2) -> bool> Shadowcast<'_, O, V> {
+    fn compute(&mut self) {
+        if !self.visible.in_bounds(self.origin) {
+            return;
+        }
+        self.visible.set_true(self.origin);
+        for quadrant in 0..4 {
+            self.scan(
+                quadrant,
+                Row {
+                    depth: 1,
+                    start: Slope::new(-1, 1),
+                    end: Slope::new(1, 1),
+                },
+            );
+        }
+    }
+
+    fn scan(&mut self, quadrant: usize, mut row: Row) {
+        if row.depth > self.radius {
+            return;
+        }
+        let mut prev_wall = None;
+        for col in row.min_col()..=row.max_col() {
+            let p = self.origin + transform(quadrant, row.depth, col);
+            let wall = !self.visible.in_bounds(p) || (self.is_opaque)(p);
+            if wall || row.is_symmetric(col) {
+                self.reveal(p);
+            }
+            if prev_wall == Some(true) && !wall {
+                row.start = Slope::from_tile(row.depth, col);
+            }
+            if prev_wall == Some(false) && wall {
+                let mut next = row.next();
+                next.end = Slope::from_tile(row.depth, col);
+                self.scan(quadrant, next);
+            }
+            prev_wall = Some(wall);
+        }
+        if prev_wall == Some(false) {
+            self.scan(quadrant, row.next());
+        }
+    }
+
+    fn reveal(&mut self, p: IVec2) {
+        let d = p - self.origin;
+        let in_radius = d.x * d.x + d.y * d.y <= self.radius * self.radius + self.radius;
+        if in_radius && self.visible.in_bounds(p) && (self.in_view)(d) {
+            self.visible.set_true(p);
+        }
+    }
+}
+
+/// Transform a row/column within a quadrant to an offset from the origin.
+#[inline]
+fn transform(quadrant: usize, depth: i32, col: i32) -> IVec2 {
+    match quadrant {
+        0 => IVec2::new(col, depth),
+        1 => IVec2::new(col, -depth),
+        2 => IVec2::new(depth, col),
+        _ => IVec2::new(-depth, col),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    depth: i32,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    fn min_col(&self) -> i32 {
+        // Round ties up: floor(depth * slope + 0.5)
+        let Slope { num, den } = self.start;
+        (2 * self.depth * num + den).div_euclid(2 * den)
+    }
+
+    fn max_col(&self) -> i32 {
+        // Round ties down: ceil(depth * slope - 0.5)
+        let Slope { num, den } = self.end;
+        -(den - 2 * self.depth * num).div_euclid(2 * den)
+    }
+
+    fn is_symmetric(&self, col: i32) -> bool {
+        col * self.start.den >= self.depth * self.start.num
+            && col * self.end.den <= self.depth * self.end.num
+    }
+
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+/// An exact slope, `den` is always positive.
+#[derive(Debug, Clone, Copy)]
+struct Slope {
+    num: i32,
+    den: i32,
+}
+
+impl Slope {
+    fn new(num: i32, den: i32) -> Self {
+        Self { num, den }
+    }
+
+    /// The slope of the left edge of a tile.
+    fn from_tile(depth: i32, col: i32) -> Self {
+        Self::new(2 * col - 1, 2 * depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_room() {
+        let opaque = BitGrid::new([21, 21]);
+        let mut visible = BitGrid::new([21, 21]);
+        shadowcast(&opaque, [10, 10], 5, &mut visible);
+
+        for (p, v) in visible.iter_xy() {
+            assert_eq!(p.distance_squared(IVec2::splat(10)) <= 30, v);
+        }
+    }
+
+    #[test]
+    fn wall_blocks() {
+        let mut opaque = BitGrid::new([10, 10]);
+        for y in 0..10 {
+            opaque.set_true([5, y]);
+        }
+        let mut visible = BitGrid::new([10, 10]);
+        shadowcast(&opaque, [2, 5], 20, &mut visible);
+
+        assert!(visible.get([0, 0]));
+        assert!(visible.get([5, 5]));
+        for y in 0..10 {
+            for x in 6..10 {
+                assert!(!visible.get([x, y]));
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric() {
+        let mut opaque = BitGrid::new([12, 12]);
+        for p in [[3, 3], [4, 7], [8, 2], [6, 6], [9, 9], [2, 9], [7, 4]] {
+            opaque.set_true(p);
+        }
+        let floors: Vec<_> = opaque
+            .iter_xy()
+            .filter(|(_, v)| !v)
+            .map(|(p, _)| p)
+            .collect();
+        let views: Vec<_> = floors
+            .iter()
+            .map(|p| {
+                let mut visible = BitGrid::new([12, 12]);
+                shadowcast(&opaque, *p, 20, &mut visible);
+                visible
+            })
+            .collect();
+
+        for (a, from_a) in floors.iter().zip(&views) {
+            for (b, from_b) in floors.iter().zip(&views) {
+                if from_a.get(*b) {
+                    assert!(from_b.get(*a), "{a} sees {b} but not the reverse");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cone() {
+        let opaque = BitGrid::new([21, 21]);
+        let mut visible = BitGrid::new([21, 21]);
+        shadowcast_cone(&opaque, GridCone::new([10, 10], 0.0, 90.0, 8), &mut visible);
+
+        assert!(visible.get([10, 10]));
+        assert!(visible.get([18, 10]));
+        assert!(visible.get([15, 14]));
+        assert!(!visible.get([9, 10]));
+        assert!(!visible.get([10, 15]));
+        assert!(!visible.get([15, 16]));
+    }
+}
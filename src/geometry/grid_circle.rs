@@ -7,6 +7,7 @@ use super::{grid_rect::GridRectIter, GridRect};
 
 /// A filled circle of points on a 2d grid.
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridCircle {
     pub center: IVec2,
     pub radius: usize,
@@ -96,6 +97,7 @@ impl IntoIterator for GridCircle {
 
 /// A hollow circle of points on a 2d grid.
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridCircleOutline {
     center: IVec2,
     radius: usize,
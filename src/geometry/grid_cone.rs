@@ -4,6 +4,7 @@ use crate::GridPoint;
 
 /// A cone/triangle of points on a 2d grid.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridCone {
     pub pos: IVec2,
     /// Angle of the cone in radians
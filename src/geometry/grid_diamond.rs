@@ -7,6 +7,7 @@ use crate::{
 
 /// A diamond of points on a 2d grid.
 #[derive(Default, Clone, Debug, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridDiamond {
     pub pos: IVec2,
     pub radius: usize,
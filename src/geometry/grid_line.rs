@@ -8,6 +8,7 @@ use super::{GridRect, GridShape};
 
 /// A line of points on a 2d grid.
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridLine {
     pub start: IVec2,
     pub end: IVec2,
@@ -121,6 +122,7 @@ fn diag_distance(p1: IVec2, p2: IVec2) -> i32 {
 /// Unlike [GridLine] every point on this line is orthogonal to the next so
 /// there are no diagonal jumps between points.
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridLineOrtho {
     start: IVec2,
     end: IVec2,
@@ -7,6 +7,7 @@ use crate::{GridPoint, GridSize, Pivot, PivotedPoint};
 
 /// A rectangle of points on a 2d grid.
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridRect {
     /// The bottom-left most tile of the rect.
     pub pos: IVec2,
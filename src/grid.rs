@@ -1,7 +1,10 @@
-//! A rectangular grid that stores it's internal data in a `Vec`. The size of
-//! the grid is constant and elements cannot be removed, only changed. Provides
+//! A rectangular grid that stores it's internal data in a `Vec`. Provides
 //! very fast iteration and access speed.
 //!
+//! The grid can be resized, cropped and padded, and rows and columns can be
+//! inserted or removed. These operations move the existing data, so they are
+//! slower than accessing elements.
+//!
 //! Elements can be inserted and accessed via their 1d index, 2d index, or
 //! read/modified via iterators.
 //!
@@ -18,25 +21,61 @@
 //! assert_eq!('a', grid[0]);
 //! assert_eq!('b', grid[ [1,0] ]);
 //!
-//! grid.insert_column_at([3,2], "hello".chars());
+//! grid.write_column_at([3,2], "hello".chars());
 //! let hello: String = grid.iter_column(3).skip(2).take(5).collect();
 //!
 //! assert_eq!("hello", hello);
 //! ```
 
-use std::ops::{Index, IndexMut};
+mod view;
+
+use std::{
+    fmt::{Display, Write},
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
 
 use glam::{IVec2, UVec2};
 
-use crate::{geometry::GridRect, GridPoint, PositionedGrid, SizedGrid};
+use crate::{geometry::GridRect, BitGrid, GridPoint, GridSize, Pivot, PositionedGrid, SizedGrid};
+
+pub use view::{GridView, GridViewMut};
 
 /// A data structure for storing a 2d sized grid of data.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridData<T>"))]
 pub struct Grid<T> {
     data: Vec<T>,
     size: UVec2,
 }
 
+/// Unvalidated grid data, used to check the size of a [Grid] when it's
+/// deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridData<T> {
+    data: Vec<T>,
+    size: UVec2,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<GridData<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(grid: GridData<T>) -> Result<Self, Self::Error> {
+        let GridData { data, size } = grid;
+        if data.len() != size.x as usize * size.y as usize {
+            return Err(format!(
+                "Grid data length {} doesn't match grid size {}",
+                data.len(),
+                size
+            ));
+        }
+        Ok(Self { data, size })
+    }
+}
+
 impl<T> Default for Grid<T> {
     fn default() -> Self {
         Self {
@@ -74,17 +113,141 @@ impl<T> Grid<T> {
         }
     }
 
-    /// Insert into a row of the grid using an iterator.
+    /// Create a grid by calling `f` with the position of each element.
+    pub fn from_fn(size: impl GridSize, mut f: impl FnMut(IVec2) -> T) -> Self {
+        let size = size.to_uvec2();
+        let [w, h] = size.as_ivec2().to_array();
+        let data = (0..h)
+            .flat_map(|y| (0..w).map(move |x| IVec2::new(x, y)))
+            .map(&mut f)
+            .collect();
+        Self { data, size }
+    }
+
+    /// Create a grid from a vec of elements, ordered from the bottom left to
+    /// the top right.
     ///
-    /// Will insert up to the length of a row.
-    pub fn insert_row(&mut self, y: usize, row: impl DoubleEndedIterator<Item = T>) {
-        self.insert_row_at([0, y as i32], row);
+    /// Returns an error if the length of the vec doesn't match the size.
+    pub fn from_vec(size: impl GridSize, data: Vec<T>) -> Result<Self, GridDataError> {
+        let size = size.to_uvec2();
+        if data.len() != size.tile_count() {
+            return Err(GridDataError::Length {
+                expected: size.tile_count(),
+                found: data.len(),
+            });
+        }
+        Ok(Self { data, size })
     }
 
-    /// Insert into a row of the grid using an iterator.
+    /// Create a grid from an iterator of rows, ordered from bottom to top. Each
+    /// row is ordered from left to right.
     ///
-    /// Will insert up to the length of a row.
-    pub fn insert_row_at(&mut self, xy: impl GridPoint, row: impl Iterator<Item = T>) {
+    /// Returns an error if the rows are not all the same length.
+    pub fn from_rows<R: IntoIterator<Item = T>>(
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<Self, GridDataError> {
+        let mut data = Vec::new();
+        let mut size = UVec2::ZERO;
+        for (y, row) in rows.into_iter().enumerate() {
+            let start = data.len();
+            data.extend(row);
+            let len = data.len() - start;
+            if y == 0 {
+                size.x = len as u32;
+            } else if len != size.x as usize {
+                return Err(GridDataError::RaggedRow {
+                    row: y + 1,
+                    expected: size.x as usize,
+                    found: len,
+                });
+            }
+            size.y += 1;
+        }
+        Ok(Self { data, size })
+    }
+
+    /// Create a grid from a multi-line string, converting each character to a
+    /// grid value.
+    ///
+    /// The top line of the string is the top row of the grid. Empty lines at
+    /// the start and end of the string are ignored. All other lines must be
+    /// the same length.
+    pub fn from_str_with(s: &str, mut f: impl FnMut(char) -> T) -> Result<Self, ParseGridError> {
+        let lines: Vec<_> = s.lines().collect();
+        let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(0);
+        let end = lines
+            .iter()
+            .rposition(|l| !l.is_empty())
+            .map_or(0, |i| i + 1);
+        let lines = &lines[start..end];
+
+        let width = lines.first().map_or(0, |l| l.chars().count());
+        // Lines are checked top to bottom before any are converted, so the
+        // error points at the first mismatched line.
+        for (i, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != width {
+                return Err(ParseGridError {
+                    line: start + i + 1,
+                    expected: width,
+                    found,
+                });
+            }
+        }
+        let mut data = Vec::with_capacity(width * lines.len());
+        for line in lines.iter().rev() {
+            data.extend(line.chars().map(&mut f));
+        }
+        Ok(Self::from_parts(
+            data,
+            UVec2::new(width as u32, lines.len() as u32),
+        ))
+    }
+
+    /// Render the grid to a multi-line string, converting each grid value to
+    /// a character.
+    ///
+    /// The top row of the grid is the first line of the string.
+    pub fn to_string_with(&self, f: impl Fn(&T) -> char) -> String {
+        let mut out = String::with_capacity((self.width() + 1) * self.height());
+        for y in (0..self.height()).rev() {
+            out.extend(self.iter_row(y).map(&f));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Insert a new row at `y`, shifting the rows at and above it up by one.
+    ///
+    /// Takes up to the width of the grid from the iterator, any remaining
+    /// values in the row are set to default.
+    ///
+    /// Panics if `y` is greater than the height of the grid.
+    ///
+    /// Before 0.7.0 this overwrote the existing row at `y` in place, use
+    /// [Grid::write_row_at] for that behavior.
+    pub fn insert_row(&mut self, y: usize, row: impl IntoIterator<Item = T>)
+    where
+        T: Default,
+    {
+        assert!(
+            y <= self.height(),
+            "Attempting to insert a row at {} in a grid of height {}",
+            y,
+            self.height()
+        );
+        let w = self.width();
+        let mut row = row.into_iter();
+        let row = (0..w).map(|_| row.next().unwrap_or_default());
+        self.data.splice(y * w..y * w, row);
+        self.size.y += 1;
+    }
+
+    /// Write into a row of the grid using an iterator, starting from the
+    /// given position. Existing values are overwritten.
+    ///
+    /// Will write up to the end of the row.
+    pub fn write_row_at(&mut self, xy: impl GridPoint, row: impl IntoIterator<Item = T>) {
         let [x, y] = xy.to_array();
         let iter = self.iter_row_mut(y as usize).skip(x as usize);
         for (v, input) in iter.zip(row) {
@@ -92,17 +255,53 @@ impl<T> Grid<T> {
         }
     }
 
-    /// Insert into a column of the grid using an iterator.
+    /// Write into a row of the grid using an iterator, starting from the
+    /// given position. Existing values are overwritten.
+    #[deprecated(
+        since = "0.7.0",
+        note = "use `write_row_at` instead, `insert_row` now inserts a new row rather than overwriting one"
+    )]
+    pub fn insert_row_at(&mut self, xy: impl GridPoint, row: impl Iterator<Item = T>) {
+        self.write_row_at(xy, row);
+    }
+
+    /// Insert a new column at `x`, shifting the columns at and to the right of
+    /// it over by one.
+    ///
+    /// Takes up to the height of the grid from the iterator, any remaining
+    /// values in the column are set to default.
     ///
-    /// Will insert up to the height of a column.
-    pub fn insert_column(&mut self, x: usize, column: impl IntoIterator<Item = T>) {
-        self.insert_column_at([x as i32, 0], column);
+    /// Panics if `x` is greater than the width of the grid.
+    ///
+    /// Before 0.7.0 this overwrote the existing column at `x` in place, use
+    /// [Grid::write_column_at] for that behavior.
+    pub fn insert_column(&mut self, x: usize, column: impl IntoIterator<Item = T>)
+    where
+        T: Default,
+    {
+        assert!(
+            x <= self.width(),
+            "Attempting to insert a column at {} in a grid of width {}",
+            x,
+            self.width()
+        );
+        let [w, h] = [self.width(), self.height()];
+        let mut old = std::mem::take(&mut self.data).into_iter();
+        let mut column = column.into_iter();
+        self.data.reserve((w + 1) * h);
+        for _ in 0..h {
+            self.data.extend(old.by_ref().take(x));
+            self.data.push(column.next().unwrap_or_default());
+            self.data.extend(old.by_ref().take(w - x));
+        }
+        self.size.x += 1;
     }
 
-    /// Insert into a column of the grid using an iterator.
+    /// Write into a column of the grid using an iterator, starting from the
+    /// given position. Existing values are overwritten.
     ///
-    /// Will insert up to the height of a column.
-    pub fn insert_column_at(&mut self, xy: impl GridPoint, column: impl IntoIterator<Item = T>) {
+    /// Will write up to the top of the column.
+    pub fn write_column_at(&mut self, xy: impl GridPoint, column: impl IntoIterator<Item = T>) {
         let [x, y] = xy.to_array();
         let iter = self.iter_column_mut(x as usize).skip(y as usize);
         for (v, input) in iter.zip(column) {
@@ -110,6 +309,117 @@ impl<T> Grid<T> {
         }
     }
 
+    /// Write into a column of the grid using an iterator, starting from the
+    /// given position. Existing values are overwritten.
+    #[deprecated(
+        since = "0.7.0",
+        note = "use `write_column_at` instead, `insert_column` now inserts a new column rather than overwriting one"
+    )]
+    pub fn insert_column_at(&mut self, xy: impl GridPoint, column: impl IntoIterator<Item = T>) {
+        self.write_column_at(xy, column);
+    }
+
+    /// Remove the row at `y`, shifting the rows above it down by one.
+    ///
+    /// Returns the removed row from left to right.
+    pub fn remove_row(&mut self, y: usize) -> Vec<T> {
+        assert!(
+            y < self.height(),
+            "Attempting to remove row {} from a grid of height {}",
+            y,
+            self.height()
+        );
+        let w = self.width();
+        let row = self.data.drain(y * w..(y + 1) * w).collect();
+        self.size.y -= 1;
+        row
+    }
+
+    /// Remove the column at `x`, shifting the columns to the right of it over
+    /// by one.
+    ///
+    /// Returns the removed column from bottom to top.
+    pub fn remove_column(&mut self, x: usize) -> Vec<T> {
+        assert!(
+            x < self.width(),
+            "Attempting to remove column {} from a grid of width {}",
+            x,
+            self.width()
+        );
+        let [w, h] = [self.width(), self.height()];
+        let mut old = std::mem::take(&mut self.data).into_iter();
+        let mut column = Vec::with_capacity(h);
+        self.data.reserve((w - 1) * h);
+        for _ in 0..h {
+            self.data.extend(old.by_ref().take(x));
+            column.extend(old.next());
+            self.data.extend(old.by_ref().take(w - x - 1));
+        }
+        self.size.x -= 1;
+        column
+    }
+
+    /// Resize the grid, keeping the existing contents anchored to the given
+    /// pivot. New positions are set to `fill`, and contents that no longer fit
+    /// are dropped.
+    pub fn resize(&mut self, size: impl GridSize, fill: T, anchor: Pivot)
+    where
+        T: Clone,
+    {
+        let size = size.to_uvec2();
+        let offset = anchor.pivot_position(size) - anchor.pivot_position(self.size);
+        self.reframe(size, offset, fill);
+    }
+
+    /// Shrink the grid to the given rect, which is clipped to the bounds of the
+    /// grid. The bottom left of the rect becomes the bottom left of the grid.
+    ///
+    /// The grid will be empty if the rect is empty or entirely outside it.
+    pub fn crop(&mut self, rect: GridRect) {
+        if rect.size.tile_count() == 0 || !self.bounds().overlaps_rect(rect) {
+            self.data.clear();
+            self.size = UVec2::ZERO;
+            return;
+        }
+        let rect = rect.clipped(self.bounds());
+        let old = std::mem::take(&mut self.data);
+        self.data = self
+            .bounds()
+            .iter_points()
+            .zip(old)
+            .filter_map(|(p, v)| rect.contains_point(p).then_some(v))
+            .collect();
+        self.size = rect.size;
+    }
+
+    /// Grow the grid by the given amount on every side, setting the new
+    /// positions to `fill`.
+    pub fn pad(&mut self, amount: u32, fill: T)
+    where
+        T: Clone,
+    {
+        let size = self.size + amount * 2;
+        self.reframe(size, IVec2::splat(amount as i32), fill);
+    }
+
+    /// Move the contents of the grid into a grid of a new size, with the
+    /// existing contents moved by `offset`.
+    fn reframe(&mut self, size: UVec2, offset: IVec2, fill: T)
+    where
+        T: Clone,
+    {
+        let old_bounds = self.bounds();
+        let old = std::mem::replace(&mut self.data, vec![fill; size.tile_count()]);
+        self.size = size;
+        for (p, v) in old_bounds.iter_points().zip(old) {
+            let p = p + offset;
+            if self.in_bounds(p) {
+                let i = self.transform_lti(p);
+                self.data[i] = v;
+            }
+        }
+    }
+
     /// Try to retrieve the value at the given position.
     ///
     /// Returns `None` if the position is out of bounds.
@@ -232,6 +542,137 @@ impl<T> Grid<T> {
     pub fn bounds(&self) -> GridRect {
         GridRect::new([0, 0], self.size)
     }
+
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`.
+    ///
+    /// The area is clipped to the bounds of both grids.
+    pub fn blit(&mut self, src: &Grid<T>, src_rect: GridRect, dest_pos: impl GridPoint)
+    where
+        T: Clone,
+    {
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        let w = rect.width();
+        for y in rect.bottom()..=rect.top() {
+            let s = src.transform_lti([rect.left(), y]);
+            let d = self.transform_lti(IVec2::new(rect.left(), y) + offset);
+            self.data[d..d + w].clone_from_slice(&src.data[s..s + w]);
+        }
+    }
+
+    /// Combine a rectangular area of another grid with this one, with the
+    /// bottom left of the area placed at `dest_pos`.
+    ///
+    /// `blend` is called with the existing value and the value from `src`. The
+    /// area is clipped to the bounds of both grids.
+    pub fn blit_with(
+        &mut self,
+        src: &Grid<T>,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        blend: impl Fn(&T, &T) -> T,
+    ) {
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points() {
+            let d = self.transform_lti(p + offset);
+            self.data[d] = blend(&self.data[d], &src.data[src.transform_lti(p)]);
+        }
+    }
+
+    /// Copy a rectangular area of another grid into this one, with the bottom
+    /// left of the area placed at `dest_pos`. Only values where the bit at the
+    /// same position in `mask` is set are copied.
+    ///
+    /// The mask must be the same size as `src`. The area is clipped to the
+    /// bounds of both grids.
+    pub fn blit_masked(
+        &mut self,
+        src: &Grid<T>,
+        src_rect: GridRect,
+        dest_pos: impl GridPoint,
+        mask: &BitGrid,
+    ) where
+        T: Clone,
+    {
+        assert_eq!(
+            src.size,
+            mask.size(),
+            "Attempting to blit with a mask of a different size than the source grid"
+        );
+        let Some((rect, offset)) = clip_blit(src.size, src_rect, self.size, dest_pos) else {
+            return;
+        };
+        for p in rect.iter_points().filter(|p| mask.get(*p)) {
+            let d = self.transform_lti(p + offset);
+            self.data[d] = src.data[src.transform_lti(p)].clone();
+        }
+    }
+
+    /// Create a new grid by calling `f` on each element.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::from_parts(self.data.iter().map(f).collect(), self.size)
+    }
+
+    /// Create a new grid by calling `f` on each element along with it's
+    /// position.
+    pub fn map_with_pos<U>(&self, mut f: impl FnMut(IVec2, &T) -> U) -> Grid<U> {
+        let data = self.iter_xy().map(|(p, v)| f(p, v)).collect();
+        Grid::from_parts(data, self.size)
+    }
+
+    /// Create a new grid by calling `f` on the elements at the same position in
+    /// both grids.
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        assert_eq!(
+            self.size, other.size,
+            "Attempting to zip grids of different sizes"
+        );
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| f(a, b))
+            .collect();
+        Grid::from_parts(data, self.size)
+    }
+
+    /// Consume the grid, returning it's elements ordered from the bottom left
+    /// to the top right.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Create a grid directly from it's data and size. The length of the data
+    /// must match the size of the grid.
+    pub(crate) fn from_parts(data: Vec<T>, size: UVec2) -> Self {
+        debug_assert_eq!(data.len(), size.x as usize * size.y as usize);
+        Self { data, size }
+    }
+}
+
+/// Clip a copy of `src_rect` from a grid of size `src_size` to `dest_pos` in a
+/// grid of size `dest_size`.
+///
+/// Returns the clipped source rect and the offset from source positions to
+/// destination positions, or [None] if nothing would be copied.
+pub(crate) fn clip_blit(
+    src_size: UVec2,
+    src_rect: GridRect,
+    dest_size: UVec2,
+    dest_pos: impl GridPoint,
+) -> Option<(GridRect, IVec2)> {
+    let clip = |a: GridRect, b: GridRect| {
+        let empty = a.size.tile_count() == 0 || b.size.tile_count() == 0;
+        (!empty && a.overlaps_rect(b)).then(|| a.clipped(b))
+    };
+    let offset = dest_pos.to_ivec2() - src_rect.pos;
+    let rect = clip(src_rect, GridRect::new([0, 0], src_size))?;
+    let rect = clip(rect.translated(offset), GridRect::new([0, 0], dest_size))?;
+    Some((rect.translated(-offset), offset))
 }
 
 impl<T> SizedGrid for Grid<T> {
@@ -240,7 +681,94 @@ impl<T> SizedGrid for Grid<T> {
     }
 }
 
-impl<T: Clone, P: GridPoint> Index<P> for Grid<T> {
+impl FromStr for Grid<char> {
+    type Err = ParseGridError;
+
+    /// Create a grid from a multi-line string. See [Grid::from_str_with].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_with(s, |c| c)
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in (0..self.height()).rev() {
+            for v in self.iter_row(y) {
+                v.fmt(f)?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when parsing a [Grid] from a string with lines of
+/// different lengths.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ParseGridError {
+    /// The line number, starting from 1, of the first line that doesn't
+    /// match the width of the grid.
+    pub line: usize,
+    /// The width of the grid, taken from the first line.
+    pub expected: usize,
+    /// The length of the mismatched line.
+    pub found: usize,
+}
+
+impl std::fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {} has length {} but the grid width is {}",
+            self.line, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ParseGridError {}
+
+/// An error returned when creating a [Grid] from data that doesn't fit the
+/// size of the grid.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GridDataError {
+    /// The length of the data doesn't match the size of the grid.
+    Length { expected: usize, found: usize },
+    /// A row doesn't match the length of the first row.
+    RaggedRow {
+        /// The row number, starting from 1 in the order the rows were given,
+        /// of the first row that doesn't match the width of the grid.
+        row: usize,
+        /// The width of the grid, taken from the first row.
+        expected: usize,
+        /// The length of the mismatched row.
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for GridDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridDataError::Length { expected, found } => write!(
+                f,
+                "Grid data length {} doesn't match the grid size, expected {}",
+                found, expected
+            ),
+            GridDataError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has length {} but the grid width is {}",
+                row, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridDataError {}
+
+impl<T, P: GridPoint> Index<P> for Grid<T> {
     type Output = T;
 
     fn index(&self, p: P) -> &Self::Output {
@@ -249,10 +777,7 @@ impl<T: Clone, P: GridPoint> Index<P> for Grid<T> {
     }
 }
 
-impl<T: Clone, P: GridPoint> IndexMut<P> for Grid<T>
-where
-    T: Default,
-{
+impl<T, P: GridPoint> IndexMut<P> for Grid<T> {
     fn index_mut(&mut self, index: P) -> &mut T {
         let xy = index.to_ivec2();
         let i = self.transform_lti(xy);
@@ -260,17 +785,14 @@ where
     }
 }
 
-impl<T: Clone> Index<usize> for Grid<T> {
+impl<T> Index<usize> for Grid<T> {
     type Output = T;
 
     fn index(&self, i: usize) -> &Self::Output {
         &self.data[i]
     }
 }
-impl<T: Clone> IndexMut<usize> for Grid<T>
-where
-    T: Default,
-{
+impl<T> IndexMut<usize> for Grid<T> {
     fn index_mut(&mut self, index: usize) -> &mut T {
         &mut self.data[index]
     }
@@ -378,4 +900,216 @@ mod tests {
 
         assert_eq!(hello, "Hello");
     }
+
+    #[test]
+    fn write_row_column() {
+        let mut grid: Grid<char> = "abc\ndef".parse().unwrap();
+        grid.write_row_at([1, 1], "xyz".chars());
+        assert_eq!("axy\ndef\n", grid.to_string());
+        grid.write_column_at([2, 0], "12".chars());
+        assert_eq!("ax2\nde1\n", grid.to_string());
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut grid: Grid<char> = "abc\ndef".parse().unwrap();
+        grid.insert_row(1, "xyz".chars());
+        assert_eq!("abc\nxyz\ndef\n", grid.to_string());
+        grid.insert_column(3, "12".chars());
+        assert_eq!("abc\0\nxyz2\ndef1\n", grid.to_string());
+
+        assert_eq!(vec!['1', '2', '\0'], grid.remove_column(3));
+        assert_eq!(vec!['d', 'e', 'f'], grid.remove_row(0));
+        assert_eq!("abc\nxyz\n", grid.to_string());
+        assert_eq!(vec!['y', 'b'], grid.remove_column(1));
+        assert_eq!("ac\nxz\n", grid.to_string());
+    }
+
+    #[test]
+    fn resize() {
+        let mut grid: Grid<char> = "ab\ncd".parse().unwrap();
+        grid.resize([3, 3], '.', Pivot::TopLeft);
+        assert_eq!("ab.\ncd.\n...\n", grid.to_string());
+
+        let mut grid: Grid<char> = "ab\ncd".parse().unwrap();
+        grid.resize([4, 3], '.', Pivot::BottomRight);
+        assert_eq!("....\n..ab\n..cd\n", grid.to_string());
+        grid.resize([1, 1], '.', Pivot::BottomRight);
+        assert_eq!("d\n", grid.to_string());
+
+        let mut grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+        grid.resize([1, 1], '.', Pivot::Center);
+        assert_eq!("e\n", grid.to_string());
+    }
+
+    #[test]
+    fn crop_pad() {
+        let mut grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+        grid.pad(1, '.');
+        assert_eq!(".....\n.abc.\n.def.\n.ghi.\n.....\n", grid.to_string());
+
+        grid.crop(GridRect::new([3, 3], [5, 5]));
+        assert_eq!("..\nc.\n", grid.to_string());
+        grid.crop(GridRect::new([5, 5], [1, 1]));
+        assert_eq!(UVec2::ZERO, grid.size());
+
+        for rect in [GridRect::new([1, 1], [0, 0]), GridRect::new([1, 1], [0, 2])] {
+            let mut grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+            grid.crop(rect);
+            assert_eq!(UVec2::ZERO, grid.size());
+            assert!(grid.data.is_empty());
+        }
+        let mut grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+        grid.crop(GridRect::new([1, 1], [2, 0]));
+        assert_eq!(UVec2::ZERO, grid.size());
+    }
+
+    #[test]
+    fn blit() {
+        let mut grid = Grid::filled('.', [4, 3]);
+        let src: Grid<char> = "abc\ndef".parse().unwrap();
+
+        grid.blit(&src, src.bounds(), [-1, 2]);
+        assert_eq!("ef..\n....\n....\n", grid.to_string());
+        grid.blit(&src, GridRect::new([1, 0], [2, 2]), [3, 0]);
+        assert_eq!("ef..\n...b\n...e\n", grid.to_string());
+        grid.blit(&src, src.bounds(), [4, 0]);
+        grid.blit(&src, GridRect::new([5, 5], [2, 2]), [0, 0]);
+        assert_eq!("ef..\n...b\n...e\n", grid.to_string());
+
+        let mut grid = Grid::filled(1, [3, 3]);
+        let src = Grid::filled(2, [2, 2]);
+        grid.blit_with(&src, src.bounds(), [1, 1], |a, b| a + b);
+        assert_eq!("133\n133\n111\n", grid.to_string());
+
+        let mut mask = BitGrid::new([2, 2]);
+        mask.set_true([1, 0]);
+        grid.blit_masked(&Grid::filled(7, [2, 2]), src.bounds(), [0, 0], &mask);
+        assert_eq!("133\n133\n171\n", grid.to_string());
+    }
+
+    #[test]
+    fn construct() {
+        let grid = Grid::from_fn([3, 2], |p| p.x + p.y * 10);
+        assert_eq!(12, grid[[2, 1]]);
+        assert_eq!(vec![0, 1, 2, 10, 11, 12], grid.clone().into_vec());
+
+        let empty = Grid::<u8>::from_fn([0, 3], |_| 1);
+        assert_eq!(UVec2::new(0, 3), empty.size());
+        assert!(empty.into_vec().is_empty());
+        let empty = Grid::<u8>::from_fn([3, 0], |_| 1);
+        assert_eq!(UVec2::new(3, 0), empty.size());
+        assert!(empty.into_vec().is_empty());
+
+        let rows = Grid::from_rows([[0, 1, 2], [10, 11, 12]]).unwrap();
+        assert_eq!(grid, rows);
+        assert_eq!(
+            grid,
+            Grid::from_vec([3, 2], grid.clone().into_vec()).unwrap()
+        );
+        assert_eq!(
+            Err(GridDataError::Length {
+                expected: 6,
+                found: 5
+            }),
+            Grid::from_vec([3, 2], vec![0; 5])
+        );
+        assert_eq!(
+            Err(GridDataError::RaggedRow {
+                row: 2,
+                expected: 2,
+                found: 1
+            }),
+            Grid::from_rows(vec![vec![1, 2], vec![3]])
+        );
+        assert_eq!(
+            UVec2::ZERO,
+            Grid::<u8>::from_rows(Vec::<Vec<u8>>::new()).unwrap().size()
+        );
+    }
+
+    #[test]
+    fn map_zip() {
+        let grid: Grid<char> = "#.\n.#".parse().unwrap();
+        let walkable = grid.map(|c| *c == '.');
+        assert!(walkable[[1, 1]]);
+        assert!(!walkable[[0, 1]]);
+
+        let positions = grid.map_with_pos(|p, _| p);
+        assert_eq!(IVec2::new(1, 0), positions[[1, 0]]);
+
+        let zipped = grid.zip_with(&walkable, |c, w| if *w { 'w' } else { *c });
+        assert_eq!("#w\nw#\n", zipped.to_string());
+    }
+
+    #[test]
+    fn index_any_type() {
+        struct Tile(u8);
+
+        let mut grid = Grid::from_fn([2, 2], |p| Tile(p.x as u8));
+        grid[[0, 1]].0 = 7;
+        grid[3] = Tile(9);
+        assert_eq!(7, grid[[0, 1]].0);
+        assert_eq!(9, grid[[1, 1]].0);
+    }
+
+    #[test]
+    fn parse() {
+        let grid: Grid<char> = "\n#..\n.#.\n".parse().unwrap();
+        assert_eq!([3, 2], grid.size().to_array());
+        assert_eq!('#', grid[[0, 1]]);
+        assert_eq!('#', grid[[1, 0]]);
+
+        let walls = Grid::from_str_with("#.\n..", |c| c == '#').unwrap();
+        assert!(walls[[0, 1]]);
+        assert!(!walls[[0, 0]]);
+    }
+
+    #[test]
+    fn parse_ragged() {
+        let err = "###\n#.\n###".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(
+            ParseGridError {
+                line: 2,
+                expected: 3,
+                found: 2
+            },
+            err
+        );
+
+        // With several ragged lines the first one from the top is reported.
+        let err = "\n###\n#.\n#\n###".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(
+            ParseGridError {
+                line: 3,
+                expected: 3,
+                found: 2
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn render() {
+        let map = "#..\n.#.\n";
+        let grid: Grid<char> = map.parse().unwrap();
+        assert_eq!(map, grid.to_string());
+
+        let rendered = grid.to_string_with(|c| if *c == '#' { 'X' } else { '_' });
+        assert_eq!("X__\n_X_\n", rendered);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = Grid::new([3, 2]);
+        grid[[2, 1]] = 5;
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: Grid<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(grid, loaded);
+
+        let wrong_size = r#"{"data":[1,2,3],"size":[2,2]}"#;
+        assert!(serde_json::from_str::<Grid<i32>>(wrong_size).is_err());
+    }
 }
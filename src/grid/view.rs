@@ -0,0 +1,591 @@
+//! Borrowed rectangular windows into a [Grid].
+//!
+//! Views use local coordinates, with `[0,0]` at the bottom left of the view.
+//! A [GridViewMut] can be split into disjoint views, which can be modified in
+//! parallel.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{Grid, GridRect};
+//!
+//! let mut grid = Grid::filled(0, [8, 8]);
+//!
+//! let mut view = grid.view_mut(GridRect::new([2, 2], [4, 4]));
+//! view[[0, 0]] = 1;
+//!
+//! let (mut left, mut right) = view.split_at_column(2);
+//! left[[1, 3]] = 2;
+//! right[[0, 0]] = 3;
+//!
+//! assert_eq!(1, grid[[2, 2]]);
+//! assert_eq!(2, grid[[3, 5]]);
+//! assert_eq!(3, grid[[4, 2]]);
+//! ```
+
+use std::{
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use glam::{IVec2, UVec2};
+
+use crate::{GridPoint, GridRect, GridSize, SizedGrid};
+
+use super::Grid;
+
+// Both view types uphold the same invariants, which every unsafe block below
+// relies on:
+//
+// - `ptr.add(y * stride + x)` is within, or one past the end of, the grid's
+//   data for every `x <= size.x` and `y < size.y`. Since `stride` is at least
+//   `size.x`, each row of the view is a contiguous run of values that doesn't
+//   overlap any other row.
+// - Every position in the view is borrowed from the grid for `'a`, shared for
+//   a [GridView] and exclusive for a [GridViewMut]. No two live mutable views
+//   cover the same position.
+
+/// An immutable rectangular window into a [Grid].
+pub struct GridView<'a, T> {
+    /// Points to the bottom left of the view. Every position in the view is
+    /// borrowed for `'a`.
+    ptr: *const T,
+    size: UVec2,
+    /// The width of the underlying grid.
+    stride: usize,
+    marker: PhantomData<&'a T>,
+}
+
+/// A mutable rectangular window into a [Grid].
+pub struct GridViewMut<'a, T> {
+    /// Points to the bottom left of the view. Every position in the view is
+    /// exclusively borrowed for `'a`.
+    ptr: *mut T,
+    size: UVec2,
+    /// The width of the underlying grid.
+    stride: usize,
+    marker: PhantomData<&'a mut T>,
+}
+
+// SAFETY: A GridView only gives out shared references to it's values, like a
+// `&[T]`, so it can be sent to or shared with other threads if `T: Sync`.
+unsafe impl<T: Sync> Send for GridView<'_, T> {}
+// SAFETY: As above, sharing a GridView only shares `&T`.
+unsafe impl<T: Sync> Sync for GridView<'_, T> {}
+// SAFETY: A GridViewMut has exclusive access to it's values, like a
+// `&mut [T]`, so sending it to another thread is like sending the values.
+unsafe impl<T: Send> Send for GridViewMut<'_, T> {}
+// SAFETY: Every method that gives out `&mut T` takes `&mut self`, so only
+// `&T` can be reached through a `&GridViewMut`. Sharing one between threads
+// is the same as sharing `&T`, which requires `T: Sync`, matching
+// `&mut [T]: Sync`.
+unsafe impl<T: Sync> Sync for GridViewMut<'_, T> {}
+
+impl<T> Grid<T> {
+    /// Create a view into a rectangular area of the grid.
+    ///
+    /// Panics if the rect isn't entirely within the grid.
+    pub fn view(&self, rect: GridRect) -> GridView<'_, T> {
+        GridView {
+            ptr: self.data.as_ptr(),
+            size: self.size,
+            stride: self.width(),
+            marker: PhantomData,
+        }
+        .view(rect)
+    }
+
+    /// Create a mutable view into a rectangular area of the grid.
+    ///
+    /// Panics if the rect isn't entirely within the grid.
+    pub fn view_mut(&mut self, rect: GridRect) -> GridViewMut<'_, T> {
+        GridViewMut {
+            ptr: self.data.as_mut_ptr(),
+            size: self.size,
+            stride: self.width(),
+            marker: PhantomData,
+        }
+        .into_view_mut(rect)
+    }
+}
+
+/// Assert that a rect is entirely within a view of the given size.
+fn assert_contains(size: UVec2, rect: GridRect) {
+    assert!(
+        GridRect::new([0, 0], size).contains_rect(rect),
+        "Attempting to create a view of {} outside a grid of size {}",
+        rect,
+        size
+    );
+}
+
+/// The offset from the bottom left of a view to the bottom left of a rect
+/// within it. Empty rects have no offset, since they may start past the end of
+/// the grid's data.
+fn offset(rect: GridRect, stride: usize) -> usize {
+    match rect.size.tile_count() {
+        0 => 0,
+        _ => rect.pos.y as usize * stride + rect.pos.x as usize,
+    }
+}
+
+impl<'a, T> GridView<'a, T> {
+    /// Create a view into a rectangular area of this view, in local
+    /// coordinates.
+    ///
+    /// Panics if the rect isn't entirely within the view.
+    pub fn view(&self, rect: GridRect) -> GridView<'a, T> {
+        assert_contains(self.size, rect);
+        GridView {
+            // SAFETY: The rect was checked to be within this view, so the new
+            // view covers a subset of this view's positions, which are already
+            // borrowed for `'a`. Empty rects keep this view's pointer, which
+            // still satisfies the invariant for any height up to this view's.
+            ptr: unsafe { self.ptr.add(offset(rect, self.stride)) },
+            size: rect.size,
+            stride: self.stride,
+            marker: PhantomData,
+        }
+    }
+
+    /// Try to retrieve the value at the given local position.
+    ///
+    /// Returns `None` if the position is out of bounds.
+    pub fn get(&self, xy: impl GridPoint) -> Option<&'a T> {
+        if !self.in_bounds(xy) {
+            return None;
+        }
+        let xy = xy.to_ivec2();
+        let i = xy.y as usize * self.stride + xy.x as usize;
+        // SAFETY: The position is within the view, so `i` points to a value
+        // borrowed for `'a`.
+        Some(unsafe { &*self.ptr.add(i) })
+    }
+
+    /// A single row of the view, from left to right.
+    pub fn row(&self, y: usize) -> &'a [T] {
+        assert!(
+            y < self.height(),
+            "Attempting to access row {} of a view of height {}",
+            y,
+            self.height()
+        );
+        // SAFETY: `y` is within the view, so the row's `width` values from
+        // `y * stride` are all within the view and borrowed for `'a`.
+        unsafe { std::slice::from_raw_parts(self.ptr.add(y * self.stride), self.width()) }
+    }
+
+    /// Iterate over the rows of the view, from bottom to top.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [T]> {
+        let view = *self;
+        (0..self.height()).map(move |y| view.row(y))
+    }
+
+    /// Iterate over every value in the view, from the bottom left to the top
+    /// right.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        self.rows().flatten()
+    }
+
+    /// Iterate over every value in the view along with it's local position.
+    pub fn iter_xy(&self) -> impl Iterator<Item = (IVec2, &'a T)> {
+        GridRect::new([0, 0], self.size)
+            .iter_points()
+            .zip(self.iter())
+    }
+
+    /// Copy the contents of the view into a new grid.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_parts(self.iter().cloned().collect(), self.size)
+    }
+}
+
+impl<'a, T> GridViewMut<'a, T> {
+    /// Create a mutable view of the given rect from this one.
+    ///
+    /// # Safety
+    ///
+    /// The rect must be within this view, and the new view must not be used
+    /// at the same time as any other mutable access to the same positions.
+    unsafe fn sub_view(&self, rect: GridRect) -> GridViewMut<'a, T> {
+        GridViewMut {
+            // SAFETY: The caller ensures the rect is within this view, so the
+            // offset stays within the grid's data. Empty rects keep this
+            // view's pointer, which still satisfies the invariant for any
+            // height up to this view's.
+            ptr: unsafe { self.ptr.add(offset(rect, self.stride)) },
+            size: rect.size,
+            stride: self.stride,
+            marker: PhantomData,
+        }
+    }
+
+    /// Convert into a smaller mutable view, in local coordinates.
+    ///
+    /// Panics if the rect isn't entirely within the view.
+    pub fn into_view_mut(self, rect: GridRect) -> GridViewMut<'a, T> {
+        assert_contains(self.size, rect);
+        // SAFETY: The rect is within this view, which is consumed so the new
+        // view is the only access to it's positions.
+        unsafe { self.sub_view(rect) }
+    }
+
+    /// Create a mutable view into a rectangular area of this view, in local
+    /// coordinates.
+    ///
+    /// Panics if the rect isn't entirely within the view.
+    pub fn view_mut(&mut self, rect: GridRect) -> GridViewMut<'_, T> {
+        assert_contains(self.size, rect);
+        // SAFETY: The rect is within this view, which stays mutably borrowed
+        // for as long as the new view is alive.
+        unsafe { self.sub_view(rect) }
+    }
+
+    /// Create an immutable view into a rectangular area of this view, in local
+    /// coordinates.
+    ///
+    /// Panics if the rect isn't entirely within the view.
+    pub fn view(&self, rect: GridRect) -> GridView<'_, T> {
+        self.as_view().view(rect)
+    }
+
+    /// Borrow the entire view immutably.
+    pub fn as_view(&self) -> GridView<'_, T> {
+        GridView {
+            ptr: self.ptr,
+            size: self.size,
+            stride: self.stride,
+            marker: PhantomData,
+        }
+    }
+
+    /// Split the view into the rows below `y` and the rows at or above `y`.
+    pub fn split_at_row(self, y: usize) -> (GridViewMut<'a, T>, GridViewMut<'a, T>) {
+        assert!(
+            y <= self.height(),
+            "Attempting to split a view of height {} at row {}",
+            self.height(),
+            y
+        );
+        let [w, h] = [self.width() as u32, self.height() as u32];
+        let y = y as u32;
+        // SAFETY: Both rects are within this view and share no rows, and this
+        // view is consumed so the two halves are the only access to it's
+        // positions.
+        unsafe {
+            (
+                self.sub_view(GridRect::new([0, 0], [w, y])),
+                self.sub_view(GridRect::new([0, y as i32], [w, h - y])),
+            )
+        }
+    }
+
+    /// Split the view into the columns left of `x` and the columns at or right
+    /// of `x`.
+    pub fn split_at_column(self, x: usize) -> (GridViewMut<'a, T>, GridViewMut<'a, T>) {
+        assert!(
+            x <= self.width(),
+            "Attempting to split a view of width {} at column {}",
+            self.width(),
+            x
+        );
+        let [w, h] = [self.width() as u32, self.height() as u32];
+        let x = x as u32;
+        // SAFETY: Both rects are within this view and share no columns, and
+        // this view is consumed so the two halves are the only access to it's
+        // positions. Their rows interleave in memory, but never overlap.
+        unsafe {
+            (
+                self.sub_view(GridRect::new([0, 0], [x, h])),
+                self.sub_view(GridRect::new([x as i32, 0], [w - x, h])),
+            )
+        }
+    }
+
+    /// Try to retrieve the value at the given local position.
+    ///
+    /// Returns `None` if the position is out of bounds.
+    pub fn get(&self, xy: impl GridPoint) -> Option<&T> {
+        self.as_view().get(xy)
+    }
+
+    /// Try to retrieve the mutable value at the given local position.
+    ///
+    /// Returns `None` if the position is out of bounds.
+    pub fn get_mut(&mut self, xy: impl GridPoint) -> Option<&mut T> {
+        if !self.in_bounds(xy) {
+            return None;
+        }
+        let xy = xy.to_ivec2();
+        let i = xy.y as usize * self.stride + xy.x as usize;
+        // SAFETY: The position is within the view, and the view stays mutably
+        // borrowed for as long as the reference is alive.
+        Some(unsafe { &mut *self.ptr.add(i) })
+    }
+
+    /// A single row of the view, from left to right.
+    pub fn row(&self, y: usize) -> &[T] {
+        self.as_view().row(y)
+    }
+
+    /// A single mutable row of the view, from left to right.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(
+            y < self.height(),
+            "Attempting to access row {} of a view of height {}",
+            y,
+            self.height()
+        );
+        // SAFETY: `y` is within the view, so the row's `width` values from
+        // `y * stride` are all within the view. The view stays mutably
+        // borrowed for as long as the slice is alive.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.add(y * self.stride), self.width()) }
+    }
+
+    /// Iterate over the rows of the view, from bottom to top.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        let view = self.as_view();
+        (0..self.height()).map(move |y| view.row(y))
+    }
+
+    /// Iterate over the mutable rows of the view, from bottom to top.
+    pub fn rows_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut [T]> {
+        let [stride, w] = [self.stride, self.width()];
+        let ptr = self.ptr;
+        // SAFETY: Each `y` is within the view and is yielded once, and rows
+        // never overlap, so the slices are disjoint. The view stays mutably
+        // borrowed for as long as the iterator and it's slices are alive.
+        (0..self.height())
+            .map(move |y| unsafe { std::slice::from_raw_parts_mut(ptr.add(y * stride), w) })
+    }
+
+    /// Iterate over every value in the view, from the bottom left to the top
+    /// right.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.rows().flatten()
+    }
+
+    /// Iterate mutably over every value in the view, from the bottom left to
+    /// the top right.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rows_mut().flatten()
+    }
+
+    /// Iterate over every value in the view along with it's local position.
+    pub fn iter_xy(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        GridRect::new([0, 0], self.size)
+            .iter_points()
+            .zip(self.iter())
+    }
+
+    /// Iterate mutably over every value in the view along with it's local
+    /// position.
+    pub fn iter_xy_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut T)> {
+        GridRect::new([0, 0], self.size)
+            .iter_points()
+            .zip(self.iter_mut())
+    }
+
+    /// Copy the contents of the view into a new grid.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.as_view().to_grid()
+    }
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<T> SizedGrid for GridView<'_, T> {
+    fn size(&self) -> UVec2 {
+        self.size
+    }
+}
+
+impl<T> SizedGrid for GridViewMut<'_, T> {
+    fn size(&self) -> UVec2 {
+        self.size
+    }
+}
+
+impl<T, P: GridPoint> Index<P> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, xy: P) -> &T {
+        let xy = xy.to_ivec2();
+        self.get(xy).unwrap_or_else(|| {
+            panic!(
+                "Attempting to index position {} outside a view of size {}",
+                xy, self.size
+            )
+        })
+    }
+}
+
+impl<T, P: GridPoint> Index<P> for GridViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, xy: P) -> &T {
+        let xy = xy.to_ivec2();
+        let size = self.size;
+        self.get(xy).unwrap_or_else(|| {
+            panic!(
+                "Attempting to index position {} outside a view of size {}",
+                xy, size
+            )
+        })
+    }
+}
+
+impl<T, P: GridPoint> IndexMut<P> for GridViewMut<'_, T> {
+    fn index_mut(&mut self, xy: P) -> &mut T {
+        let xy = xy.to_ivec2();
+        let size = self.size;
+        self.get_mut(xy).unwrap_or_else(|| {
+            panic!(
+                "Attempting to index position {} outside a view of size {}",
+                xy, size
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers() -> Grid<u32> {
+        Grid::from_parts((0..30).collect(), UVec2::new(6, 5))
+    }
+
+    #[test]
+    fn view() {
+        let grid = numbers();
+        let view = grid.view(GridRect::new([1, 1], [4, 3]));
+        assert_eq!(UVec2::new(4, 3), view.size());
+        assert_eq!(7, view[[0, 0]]);
+        assert_eq!(Some(&22), view.get([3, 2]));
+        assert_eq!(None, view.get([4, 0]));
+        assert_eq!(&[13, 14, 15, 16], view.row(1));
+        assert_eq!(3, view.rows().count());
+
+        let inner = view.view(GridRect::new([1, 1], [2, 2]));
+        assert_eq!(
+            vec![14, 15, 20, 21],
+            inner.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            grid.view(GridRect::new([2, 2], [2, 2])).to_grid(),
+            inner.to_grid()
+        );
+        assert_eq!(0, view.view(GridRect::new([4, 3], [0, 0])).iter().count());
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut grid = numbers();
+        let rect = GridRect::new([1, 1], [4, 3]);
+        let outside = [[0, -1], [-1, 1], [-1, -1], [4, 0], [0, 3], [4, 3]];
+
+        let view = grid.view(rect);
+        for p in outside {
+            assert_eq!(None, view.get(p), "{p:?}");
+        }
+        assert_eq!(Some(&22), view.get([3, 2]));
+
+        let mut view = grid.view_mut(rect);
+        for p in outside {
+            assert_eq!(None, view.get(p), "{p:?}");
+            assert_eq!(None, view.get_mut(p), "{p:?}");
+        }
+        assert_eq!(Some(&mut 7), view.get_mut([0, 0]));
+    }
+
+    #[test]
+    fn view_mut() {
+        let mut grid = numbers();
+        let mut view = grid.view_mut(GridRect::new([2, 1], [3, 3]));
+        view[[0, 0]] = 100;
+        view.row_mut(2).fill(0);
+        view.view_mut(GridRect::new([1, 0], [2, 2]))
+            .iter_mut()
+            .for_each(|v| *v += 1000);
+        assert_eq!(1009, view[[1, 0]]);
+        assert_eq!(vec![100, 1009, 1010], view.row(0).to_vec());
+
+        assert_eq!(100, grid[[2, 1]]);
+        assert_eq!(0, grid[[4, 3]]);
+        assert_eq!(1016, grid[[4, 2]]);
+        assert_eq!(18, grid[[0, 3]]);
+    }
+
+    #[test]
+    fn split() {
+        let mut grid = Grid::filled(0, [8, 6]);
+        let (bottom, top) = grid.view_mut(grid.bounds()).split_at_row(2);
+        let (mut left, mut right) = top.split_at_column(5);
+        assert_eq!(UVec2::new(8, 2), bottom.size());
+        assert_eq!(UVec2::new(3, 4), right.size());
+
+        std::thread::scope(|s| {
+            s.spawn(|| left.iter_mut().for_each(|v| *v = 1));
+            s.spawn(|| right.iter_mut().for_each(|v| *v = 2));
+        });
+        assert_eq!(2 * 8, grid.iter().filter(|v| **v == 0).count());
+        assert_eq!(5 * 4, grid.iter().filter(|v| **v == 1).count());
+        assert_eq!(2, grid[[5, 2]]);
+        assert_eq!(1, grid[[4, 5]]);
+    }
+
+    #[test]
+    fn split_edges() {
+        let mut grid = numbers();
+        let view = grid.view_mut(GridRect::new([1, 1], [4, 3]));
+        let (empty, view) = view.split_at_column(0);
+        assert_eq!(UVec2::new(0, 3), empty.size());
+        assert!(empty.rows().all(|row| row.is_empty()));
+
+        let (mut bottom, empty) = view.split_at_row(3);
+        assert_eq!(0, empty.iter().count());
+        let (mut left, mut right) = bottom
+            .view_mut(GridRect::new([0, 1], [4, 2]))
+            .split_at_column(1);
+        // Hold references into both halves at once.
+        let (a, b) = (&mut left[[0, 1]], &mut right[[2, 0]]);
+        std::mem::swap(a, b);
+        assert_eq!(vec![7, 8, 9, 10], bottom.row(0).to_vec());
+        assert_eq!(vec![13, 14, 15, 19], bottom.row(1).to_vec());
+        assert_eq!(vec![16, 20, 21, 22], bottom.row(2).to_vec());
+        assert_eq!(19, grid[[4, 2]]);
+    }
+
+    #[test]
+    fn rows_mut() {
+        let mut grid = numbers();
+        let mut view = grid.view_mut(GridRect::new([2, 1], [3, 3]));
+        // Every row is borrowed at the same time.
+        let mut rows: Vec<_> = view.rows_mut().collect();
+        let (lower, upper) = rows.split_at_mut(1);
+        lower[0].swap_with_slice(upper[1]);
+        for row in rows.iter_mut().rev() {
+            row[0] = 0;
+        }
+        assert_eq!(vec![0, 21, 22], view.row(0).to_vec());
+        assert_eq!(vec![0, 15, 16], view.row(1).to_vec());
+        assert_eq!(vec![0, 9, 10], view.row(2).to_vec());
+        assert_eq!(
+            vec![18, 19, 0, 9, 10, 23],
+            grid.iter_row(3).copied().collect::<Vec<_>>()
+        );
+    }
+}
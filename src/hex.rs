@@ -0,0 +1,477 @@
+//! Hexagonal grid coordinates and storage.
+//!
+//! Hexes use [axial coordinates](https://www.redblobgames.com/grids/hexagons/#coordinates-axial)
+//! where `s` is derived from `q` and `r` to give cube coordinates when needed.
+//! A [HexGrid] stores it's data in a rectangular [Grid] using one of the
+//! [OffsetLayout]s.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::hex::{Hex, HexGrid, OffsetLayout};
+//!
+//! let a = Hex::new(0, 0);
+//! let b = Hex::new(2, 1);
+//! assert_eq!(3, a.distance(b));
+//! assert_eq!(6, a.neighbors().count());
+//!
+//! let mut grid = HexGrid::new([5, 5], OffsetLayout::OddR);
+//! grid[b] = 'b';
+//! assert_eq!(Some(&'b'), grid.get(b));
+//! assert_eq!([1, 4], OffsetLayout::OddR.to_offset(Hex::new(-1, 4)).to_array());
+//! ```
+
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+use glam::{IVec2, IVec3, UVec2, Vec2};
+
+use crate::{Grid, GridPoint, GridSize, SizedGrid};
+
+/// The six directions from a hex to it's neighbors, starting from the
+/// positive q axis and going around the hex in order.
+pub const HEX_DIRECTIONS: [Hex; 6] = [
+    Hex::new(1, 0),
+    Hex::new(1, -1),
+    Hex::new(0, -1),
+    Hex::new(-1, 0),
+    Hex::new(-1, 1),
+    Hex::new(0, 1),
+];
+
+/// A position on a hexagonal grid in axial coordinates.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl Hex {
+    pub const ZERO: Hex = Hex::new(0, 0);
+
+    pub const fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    /// Create a hex from cube coordinates.
+    ///
+    /// The coordinates must sum to zero.
+    pub fn from_cube(cube: IVec3) -> Self {
+        debug_assert_eq!(
+            0,
+            cube.x + cube.y + cube.z,
+            "Attempting to create a hex from invalid cube coordinates {}",
+            cube
+        );
+        Self::new(cube.x, cube.y)
+    }
+
+    /// The third cube coordinate, derived from `q` and `r`.
+    #[inline]
+    pub fn s(&self) -> i32 {
+        -self.q - self.r
+    }
+
+    /// The cube coordinates of this hex.
+    #[inline]
+    pub fn to_cube(&self) -> IVec3 {
+        IVec3::new(self.q, self.r, self.s())
+    }
+
+    /// Round fractional axial coordinates to the nearest hex.
+    pub fn round(frac: Vec2) -> Self {
+        let (q, r) = (frac.x, frac.y);
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        Self::new(rq as i32, rr as i32)
+    }
+
+    /// The fractional axial coordinates of this hex.
+    #[inline]
+    pub fn as_vec2(&self) -> Vec2 {
+        Vec2::new(self.q as f32, self.r as f32)
+    }
+
+    /// Returns the adjacent hex in the given direction, where directions are
+    /// indices into [HEX_DIRECTIONS].
+    #[inline]
+    pub fn neighbor(&self, dir: usize) -> Hex {
+        *self + HEX_DIRECTIONS[dir % 6]
+    }
+
+    /// Returns an iterator over the 6 hexes adjacent to this one.
+    pub fn neighbors(&self) -> impl Iterator<Item = Hex> {
+        let hex = *self;
+        HEX_DIRECTIONS.into_iter().map(move |d| hex + d)
+    }
+
+    /// The number of steps between two hexes.
+    #[inline]
+    pub fn distance(&self, other: Hex) -> usize {
+        let d = (self.to_cube() - other.to_cube()).abs();
+        d.max_element() as usize
+    }
+
+    /// Returns an iterator over the hexes in a line from this hex to another,
+    /// including both ends.
+    pub fn line_to(&self, other: Hex) -> impl Iterator<Item = Hex> {
+        let n = self.distance(other);
+        // Nudge the line so it never passes exactly between two hexes.
+        let a = self.as_vec2() + Vec2::splat(1e-6);
+        let b = other.as_vec2() + Vec2::splat(1e-6);
+        (0..=n).map(move |i| {
+            let t = if n == 0 { 0.0 } else { i as f32 / n as f32 };
+            Hex::round(a.lerp(b, t))
+        })
+    }
+
+    /// Returns an iterator over the hexes at exactly the given distance from
+    /// this one.
+    pub fn ring(&self, radius: usize) -> impl Iterator<Item = Hex> {
+        let center = *self;
+        let r = radius as i32;
+        let sides = if radius == 0 { 0 } else { 6 };
+        std::iter::once(center)
+            .filter(move |_| radius == 0)
+            .chain((0..sides).flat_map(move |side| {
+                let corner = center + HEX_DIRECTIONS[(side + 4) % 6] * r;
+                (0..r).map(move |i| corner + HEX_DIRECTIONS[side] * i)
+            }))
+    }
+
+    /// Returns an iterator over all the hexes within the given distance of
+    /// this one, starting at the center and moving outwards ring by ring.
+    pub fn spiral(&self, radius: usize) -> impl Iterator<Item = Hex> {
+        let center = *self;
+        (0..=radius).flat_map(move |r| center.ring(r))
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Hex) -> Self::Output {
+        Hex::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, rhs: Hex) -> Self::Output {
+        Hex::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Mul<i32> for Hex {
+    type Output = Hex;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Hex::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Hex;
+
+    fn neg(self) -> Self::Output {
+        Hex::new(-self.q, -self.r)
+    }
+}
+
+impl std::fmt::Display for Hex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hex({}, {})", self.q, self.r)
+    }
+}
+
+/// The layout used to map hexes to rectangular offset coordinates.
+///
+/// Row layouts are used for pointy topped hexes where every other row is
+/// shifted, column layouts are used for flat topped hexes where every other
+/// column is shifted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OffsetLayout {
+    /// Odd rows are shifted right.
+    OddR,
+    /// Even rows are shifted right.
+    EvenR,
+    /// Odd columns are shifted up.
+    OddQ,
+    /// Even columns are shifted up.
+    EvenQ,
+}
+
+impl OffsetLayout {
+    /// Convert a hex to offset coordinates.
+    pub fn to_offset(&self, hex: Hex) -> IVec2 {
+        let Hex { q, r } = hex;
+        match self {
+            OffsetLayout::OddR => IVec2::new(q + (r - (r & 1)) / 2, r),
+            OffsetLayout::EvenR => IVec2::new(q + (r + (r & 1)) / 2, r),
+            OffsetLayout::OddQ => IVec2::new(q, r + (q - (q & 1)) / 2),
+            OffsetLayout::EvenQ => IVec2::new(q, r + (q + (q & 1)) / 2),
+        }
+    }
+
+    /// Convert offset coordinates to a hex.
+    pub fn from_offset(&self, xy: impl GridPoint) -> Hex {
+        let [x, y] = xy.to_array();
+        match self {
+            OffsetLayout::OddR => Hex::new(x - (y - (y & 1)) / 2, y),
+            OffsetLayout::EvenR => Hex::new(x - (y + (y & 1)) / 2, y),
+            OffsetLayout::OddQ => Hex::new(x, y - (x - (x & 1)) / 2),
+            OffsetLayout::EvenQ => Hex::new(x, y - (x + (x & 1)) / 2),
+        }
+    }
+}
+
+/// The orientation of hexes when converting to and from pixel positions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HexOrientation {
+    /// Hexes have a corner at the top, and rows of hexes are aligned.
+    Pointy,
+    /// Hexes have a flat edge at the top, and columns of hexes are aligned.
+    Flat,
+}
+
+impl HexOrientation {
+    /// Convert a hex to the pixel position of it's center, where `size` is
+    /// the distance from the center of a hex to any corner.
+    pub fn hex_to_pixel(&self, hex: Hex, size: f32) -> Vec2 {
+        let sqrt3 = 3f32.sqrt();
+        let Vec2 { x: q, y: r } = hex.as_vec2();
+        let p = match self {
+            HexOrientation::Pointy => Vec2::new(sqrt3 * q + sqrt3 / 2.0 * r, 1.5 * r),
+            HexOrientation::Flat => Vec2::new(1.5 * q, sqrt3 / 2.0 * q + sqrt3 * r),
+        };
+        p * size
+    }
+
+    /// Convert a pixel position to the hex containing it, where `size` is the
+    /// distance from the center of a hex to any corner.
+    pub fn pixel_to_hex(&self, pixel: Vec2, size: f32) -> Hex {
+        let sqrt3 = 3f32.sqrt();
+        let Vec2 { x, y } = pixel / size;
+        let frac = match self {
+            HexOrientation::Pointy => Vec2::new(sqrt3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+            HexOrientation::Flat => Vec2::new(2.0 / 3.0 * x, -x / 3.0 + sqrt3 / 3.0 * y),
+        };
+        Hex::round(frac)
+    }
+}
+
+/// A rectangular area of hexes, stored in a [Grid] using an [OffsetLayout].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexGrid<T> {
+    grid: Grid<T>,
+    layout: OffsetLayout,
+}
+
+impl<T> HexGrid<T> {
+    /// Create a new [HexGrid] with the given size in offset coordinates.
+    pub fn new(size: impl GridSize, layout: OffsetLayout) -> Self
+    where
+        T: Default + Clone,
+    {
+        Self {
+            grid: Grid::new(size.to_uvec2()),
+            layout,
+        }
+    }
+
+    /// Creates a new [HexGrid] with all elements set to the given value.
+    pub fn filled(value: T, size: impl GridSize, layout: OffsetLayout) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            grid: Grid::filled(value, size.to_uvec2()),
+            layout,
+        }
+    }
+
+    /// The offset layout of the grid.
+    pub fn layout(&self) -> OffsetLayout {
+        self.layout
+    }
+
+    /// The underlying grid, indexed by offset coordinates.
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    /// The underlying grid, indexed by offset coordinates.
+    pub fn grid_mut(&mut self) -> &mut Grid<T> {
+        &mut self.grid
+    }
+
+    /// Returns true if the hex is inside the grid.
+    #[inline]
+    pub fn contains(&self, hex: Hex) -> bool {
+        self.grid.in_bounds(self.layout.to_offset(hex))
+    }
+
+    /// Try to retrieve the value at the given hex.
+    ///
+    /// Returns `None` if the hex is out of bounds.
+    pub fn get(&self, hex: Hex) -> Option<&T> {
+        self.grid.get(self.layout.to_offset(hex))
+    }
+
+    /// Try to retrieve the mutable value at the given hex.
+    ///
+    /// Returns `None` if the hex is out of bounds.
+    pub fn get_mut(&mut self, hex: Hex) -> Option<&mut T> {
+        self.grid.get_mut(self.layout.to_offset(hex))
+    }
+
+    /// Returns an iterator over the neighbors of a hex that are inside the
+    /// grid.
+    pub fn neighbors(&self, hex: Hex) -> impl Iterator<Item = Hex> + '_ {
+        hex.neighbors().filter(|h| self.contains(*h))
+    }
+
+    /// An iterator over all elements in the grid.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.grid.iter()
+    }
+
+    /// A mutable iterator over all elements in the grid.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        self.grid.iter_mut()
+    }
+
+    /// Iterate over all grid elements along with their hex positions.
+    pub fn iter_hex(&self) -> impl Iterator<Item = (Hex, &T)> {
+        let layout = self.layout;
+        self.grid
+            .iter_xy()
+            .map(move |(p, v)| (layout.from_offset(p), v))
+    }
+
+    /// Iterate over all grid elements along with their hex positions.
+    pub fn iter_hex_mut(&mut self) -> impl Iterator<Item = (Hex, &mut T)> {
+        let layout = self.layout;
+        self.grid
+            .iter_xy_mut()
+            .map(move |(p, v)| (layout.from_offset(p), v))
+    }
+}
+
+impl<T> SizedGrid for HexGrid<T> {
+    fn size(&self) -> UVec2 {
+        self.grid.size()
+    }
+}
+
+impl<T> Index<Hex> for HexGrid<T> {
+    type Output = T;
+
+    fn index(&self, hex: Hex) -> &Self::Output {
+        &self.grid[self.layout.to_offset(hex)]
+    }
+}
+
+impl<T> IndexMut<Hex> for HexGrid<T> {
+    fn index_mut(&mut self, hex: Hex) -> &mut T {
+        let xy = self.layout.to_offset(hex);
+        &mut self.grid[xy]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_neighbors() {
+        let hex = Hex::new(2, -3);
+        for n in hex.neighbors() {
+            assert_eq!(1, hex.distance(n));
+        }
+        assert_eq!(3, Hex::ZERO.distance(Hex::new(3, -1)));
+        assert_eq!(5, Hex::new(-2, 4).distance(Hex::new(3, -1)));
+    }
+
+    #[test]
+    fn line() {
+        let a = Hex::new(-2, 1);
+        let b = Hex::new(3, -2);
+        let line: Vec<_> = a.line_to(b).collect();
+        assert_eq!(a.distance(b) + 1, line.len());
+        assert_eq!(a, line[0]);
+        assert_eq!(b, *line.last().unwrap());
+        for pair in line.windows(2) {
+            assert_eq!(1, pair[0].distance(pair[1]));
+        }
+    }
+
+    #[test]
+    fn ring_spiral() {
+        let center = Hex::new(1, 1);
+        assert_eq!(vec![center], center.ring(0).collect::<Vec<_>>());
+        for radius in 1..5 {
+            let ring: Vec<_> = center.ring(radius).collect();
+            assert_eq!(radius * 6, ring.len());
+            assert!(ring.iter().all(|h| h.distance(center) == radius));
+        }
+        assert_eq!(37, center.spiral(3).count());
+    }
+
+    #[test]
+    fn round() {
+        assert_eq!(Hex::new(1, 0), Hex::round(Vec2::new(0.9, 0.05)));
+        assert_eq!(Hex::new(0, -1), Hex::round(Vec2::new(0.1, -0.8)));
+    }
+
+    #[test]
+    fn offset_pixel() {
+        let layouts = [
+            OffsetLayout::OddR,
+            OffsetLayout::EvenR,
+            OffsetLayout::OddQ,
+            OffsetLayout::EvenQ,
+        ];
+        for hex in Hex::new(-1, 2).spiral(4) {
+            for layout in layouts {
+                assert_eq!(hex, layout.from_offset(layout.to_offset(hex)));
+            }
+            for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+                let pixel = orientation.hex_to_pixel(hex, 16.0);
+                assert_eq!(hex, orientation.pixel_to_hex(pixel, 16.0));
+                let edge = pixel + Vec2::new(5.0, -7.0);
+                assert_eq!(hex, orientation.pixel_to_hex(edge, 16.0));
+            }
+        }
+        assert_eq!(
+            [0, 1],
+            OffsetLayout::OddR.to_offset(Hex::new(0, 1)).to_array()
+        );
+        assert_eq!(
+            [1, 1],
+            OffsetLayout::EvenR.to_offset(Hex::new(0, 1)).to_array()
+        );
+    }
+
+    #[test]
+    fn hex_grid() {
+        let mut grid = HexGrid::new([4, 3], OffsetLayout::EvenQ);
+        let hex = OffsetLayout::EvenQ.from_offset([3, 2]);
+        grid[hex] = 5;
+        assert_eq!(5, grid.grid()[[3, 2]]);
+        assert!(!grid.contains(Hex::new(4, 0)));
+        assert_eq!(None, grid.get(Hex::new(-1, 0)));
+        assert_eq!(3, grid.neighbors(Hex::new(0, 0)).count());
+        assert_eq!(Some((hex, &5)), grid.iter_hex().find(|(_, v)| **v == 5));
+    }
+}
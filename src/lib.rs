@@ -1,20 +1,38 @@
 //! Utilities for dealing with data, points and shapes on a 2d grid.
 
+pub mod automata;
+pub mod binary;
 pub mod bit_grid;
+pub mod chunked_grid;
+pub mod contour;
+pub mod dijkstra_map;
 pub mod direction;
+pub mod distance_transform;
 pub mod float_grid;
+pub mod fov;
 pub mod geometry;
 pub mod grid;
+pub mod hex;
+pub mod morphology;
+pub mod noise;
+pub mod pathfinding;
 pub mod pivot;
 pub mod point;
+pub mod region;
 pub mod size;
 pub mod sparse_grid;
+pub mod storage;
+pub mod transform;
 pub mod util;
 
 pub use bit_grid::BitGrid;
+pub use chunked_grid::ChunkedGrid;
 pub use float_grid::FloatGrid;
 pub use geometry::{GridRect, GridShape, GridShapeIterator, PositionedGrid, SizedGrid};
 pub use grid::Grid;
+pub use hex::{Hex, HexGrid};
 pub use pivot::{Pivot, PivotedPoint};
 pub use point::GridPoint;
 pub use size::GridSize;
+pub use storage::{GridRead, GridWrite};
+pub use transform::GridTransform;
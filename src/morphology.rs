@@ -0,0 +1,210 @@
+//! Morphological operations on a [BitGrid], for smoothing maps and finding
+//! clearance for agents larger than a single tile.
+//!
+//! Operations use a [GridShape] as the structuring element, where each point
+//! of the shape is an offset from world origin, so elements should usually be
+//! centered on `[0,0]`. Positions outside the grid are treated as unset.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{geometry::GridCircle, morphology, BitGrid};
+//!
+//! let mut grid = BitGrid::new([9, 9]);
+//! grid.set_true([4, 4]);
+//!
+//! let grown = morphology::dilate(&grid, &GridCircle::origin(2));
+//! assert!(grown.get([4, 6]));
+//! assert!(!grown.get([6, 6]));
+//!
+//! let shrunk = morphology::erode(&grown, &GridCircle::origin(2));
+//! assert_eq!(1, shrunk.count_ones());
+//! ```
+
+use glam::IVec2;
+
+use crate::{direction::Adjacency, BitGrid, GridShape, SizedGrid};
+
+/// Grow the set bits of a grid by a structuring element.
+///
+/// The result is the union of copies of the grid shifted by each point of the
+/// element. Equivalently, a bit is set in the result if any point of the
+/// element reflected through the origin, placed at that position, overlaps a
+/// set bit in the grid.
+pub fn dilate(grid: &BitGrid, element: &impl GridShape) -> BitGrid {
+    let mut out = BitGrid::new(grid.size());
+    for offset in element.iter() {
+        out.or_offset(grid, offset);
+    }
+    out
+}
+
+/// Shrink the set bits of a grid by a structuring element.
+///
+/// A bit is set in the result only if every point of the element, placed at
+/// that position, overlaps a set bit in the grid.
+pub fn erode(grid: &BitGrid, element: &impl GridShape) -> BitGrid {
+    erode_by(grid, element.iter())
+}
+
+/// Erode then dilate a grid, removing small features while preserving the
+/// shape of larger ones.
+pub fn open(grid: &BitGrid, element: &impl GridShape) -> BitGrid {
+    dilate(&erode(grid, element), element)
+}
+
+/// Dilate then erode a grid, filling small holes and gaps while preserving
+/// the shape of larger features.
+pub fn close(grid: &BitGrid, element: &impl GridShape) -> BitGrid {
+    erode(&dilate(grid, element), element)
+}
+
+/// Find the set bits that are adjacent to an unset bit or the edge of the
+/// grid.
+pub fn boundary<D: Adjacency>(grid: &BitGrid) -> BitGrid {
+    let interior = erode_by(grid, D::directions().iter().copied());
+    let mut out = grid.clone();
+    out.difference(&interior);
+    out
+}
+
+/// Thin the set bits of a grid down to lines a single tile wide using the
+/// Zhang-Suen algorithm.
+pub fn thin(grid: &BitGrid) -> BitGrid {
+    // Neighbors in clockwise order starting from the top.
+    const NEIGHBORS: [IVec2; 8] = [
+        IVec2::new(0, 1),
+        IVec2::new(1, 1),
+        IVec2::new(1, 0),
+        IVec2::new(1, -1),
+        IVec2::new(0, -1),
+        IVec2::new(-1, -1),
+        IVec2::new(-1, 0),
+        IVec2::new(-1, 1),
+    ];
+
+    let mut grid = grid.clone();
+    let mut remove = Vec::new();
+    loop {
+        let mut changed = false;
+        for pass in 0..2 {
+            for p in grid.iter_grid_points() {
+                if !grid.get(p) {
+                    continue;
+                }
+                let n = NEIGHBORS.map(|d| grid.in_bounds(p + d) && grid.get(p + d));
+                let count = n.iter().filter(|b| **b).count();
+                let transitions = (0..8).filter(|&i| !n[i] && n[(i + 1) % 8]).count();
+                let [top, _, right, _, bottom, _, left, _] = n;
+                let clear = match pass {
+                    0 => !(right && bottom && (top || left)),
+                    _ => !(top && left && (right || bottom)),
+                };
+                if (2..=6).contains(&count) && transitions == 1 && clear {
+                    remove.push(p);
+                }
+            }
+            changed |= !remove.is_empty();
+            for p in remove.drain(..) {
+                grid.set_false(p);
+            }
+        }
+        if !changed {
+            return grid;
+        }
+    }
+}
+
+fn erode_by(grid: &BitGrid, offsets: impl Iterator<Item = IVec2>) -> BitGrid {
+    let mut out = grid.clone();
+    let mut shifted = BitGrid::new(grid.size());
+    for offset in offsets {
+        shifted.clear();
+        shifted.or_offset(grid, -offset);
+        out.and(&shifted);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        direction::{Dir4, Dir8},
+        geometry::GridDiamond,
+        GridRect,
+    };
+
+    fn rect(size: [u32; 2], rect: GridRect) -> BitGrid {
+        let mut grid = BitGrid::new(size);
+        rect.iter_points().for_each(|p| grid.set_true(p));
+        grid
+    }
+
+    #[test]
+    fn dilate_erode() {
+        let grid = rect([10, 10], GridRect::new([3, 3], [3, 3]));
+        let element = GridDiamond::new([0, 0], 1);
+
+        let grown = dilate(&grid, &element);
+        assert_eq!(9 + 12, grown.count_ones());
+        assert!(grown.get([4, 2]));
+        assert!(!grown.get([2, 2]));
+
+        let shrunk = erode(&grid, &element);
+        assert_eq!(1, shrunk.count_ones());
+        assert!(shrunk.get([4, 4]));
+
+        let full = BitGrid::new([4, 4]).with_value(true);
+        assert_eq!(4, erode(&full, &element).count_ones());
+    }
+
+    #[test]
+    fn dilate_asymmetric() {
+        let mut grid = BitGrid::new([8, 1]);
+        grid.set_true([4, 0]);
+        let grown = dilate(&grid, &GridRect::new([0, 0], [2, 1]));
+        assert_eq!(2, grown.count_ones());
+        assert!(grown.get([4, 0]));
+        assert!(grown.get([5, 0]));
+    }
+
+    #[test]
+    fn open_close() {
+        let mut grid = rect([12, 12], GridRect::new([2, 2], [6, 6]));
+        grid.set_true([10, 10]);
+        grid.set_false([5, 5]);
+        let element = GridRect::center_origin([3, 3]);
+
+        let opened = open(&grid, &element);
+        assert!(!opened.get([10, 10]));
+        assert!(opened.get([2, 2]));
+
+        let closed = close(&grid, &element);
+        assert!(closed.get([5, 5]));
+        assert!(closed.get([10, 10]));
+    }
+
+    #[test]
+    fn boundaries() {
+        let grid = rect([6, 6], GridRect::new([0, 0], [4, 4]));
+        assert_eq!(12, boundary::<Dir4>(&grid).count_ones());
+
+        let mut grid = grid;
+        grid.set_false([1, 1]);
+        assert!(!boundary::<Dir4>(&grid).get([2, 2]));
+        assert!(boundary::<Dir8>(&grid).get([2, 2]));
+    }
+
+    #[test]
+    fn thinning() {
+        let grid = rect([12, 7], GridRect::new([1, 1], [10, 5]));
+        let thinned = thin(&grid);
+        assert!(thinned.count_ones() < 15);
+        assert!(thinned.get([5, 3]));
+        for x in 3..8 {
+            let column = (0..7).filter(|y| thinned.get([x, *y])).count();
+            assert_eq!(1, column);
+        }
+    }
+}
@@ -0,0 +1,467 @@
+//! Seeded procedural noise for filling a [FloatGrid].
+//!
+//! Noise is sampled in world space, so grids filled with the offset of their
+//! position in the world, such as the chunks of a [ChunkedGrid], will tile
+//! seamlessly.
+//!
+//! [ChunkedGrid]: crate::ChunkedGrid
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{noise::{self, Fractal, Perlin}, FloatGrid};
+//!
+//! let terrain = Fractal::fbm(Perlin::new(42)).with_octaves(5);
+//!
+//! let mut left = FloatGrid::new([16, 16]);
+//! let mut right = FloatGrid::new([16, 16]);
+//! noise::fill(&mut left, &terrain, [0, 0], 0.05);
+//! noise::fill(&mut right, &terrain, [16, 0], 0.05);
+//!
+//! let mut both = FloatGrid::new([32, 16]);
+//! noise::fill(&mut both, &terrain, [0, 0], 0.05);
+//! assert_eq!(both.value([20, 3]), right.value([4, 3]));
+//! ```
+
+use glam::{IVec2, Vec2};
+
+use crate::{FloatGrid, GridPoint, GridRect, GridSize, SizedGrid};
+
+/// A 2d noise function.
+pub trait Noise {
+    /// Sample the noise at the given position.
+    fn sample(&self, p: Vec2) -> f32;
+}
+
+impl<F: Fn(Vec2) -> f32> Noise for F {
+    fn sample(&self, p: Vec2) -> f32 {
+        self(p)
+    }
+}
+
+/// Fill a grid with noise.
+///
+/// `offset` is the world position of the grid's bottom left tile, and
+/// `frequency` scales world positions before they are sampled.
+pub fn fill(grid: &mut FloatGrid, noise: &impl Noise, offset: impl GridPoint, frequency: f32) {
+    let rect = GridRect::new([0, 0], grid.size());
+    fill_rect(grid, rect, noise, offset, frequency);
+}
+
+/// Fill a rectangular area of a grid with noise, leaving other values
+/// unchanged. The area is clipped to the bounds of the grid.
+///
+/// `offset` is the world position of the grid's bottom left tile, and
+/// `frequency` scales world positions before they are sampled.
+pub fn fill_rect(
+    grid: &mut FloatGrid,
+    rect: GridRect,
+    noise: &impl Noise,
+    offset: impl GridPoint,
+    frequency: f32,
+) {
+    let bounds = GridRect::new([0, 0], grid.size());
+    let empty = rect.size.tile_count() == 0 || bounds.size.tile_count() == 0;
+    if empty || !rect.overlaps_rect(bounds) {
+        return;
+    }
+    let rect = rect.clipped(bounds);
+    let offset = offset.to_ivec2();
+    for p in rect.iter_points() {
+        let world = (offset + p).as_vec2() * frequency;
+        grid[p] = noise.sample(world);
+    }
+}
+
+/// Smoothly interpolated random values at each integer position, in the range
+/// `-1.0..=1.0`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValueNoise {
+    pub seed: u32,
+}
+
+impl ValueNoise {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+}
+
+impl Noise for ValueNoise {
+    fn sample(&self, p: Vec2) -> f32 {
+        let cell = p.floor();
+        let t = fade(p - cell);
+        let c = cell.as_ivec2();
+        let v = |x, y| unit(hash(self.seed, c + IVec2::new(x, y)));
+        let bottom = lerp(v(0, 0), v(1, 0), t.x);
+        let top = lerp(v(0, 1), v(1, 1), t.x);
+        lerp(bottom, top, t.y)
+    }
+}
+
+/// Perlin gradient noise, roughly in the range `-1.0..=1.0`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Perlin {
+    pub seed: u32,
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+}
+
+impl Noise for Perlin {
+    fn sample(&self, p: Vec2) -> f32 {
+        let cell = p.floor();
+        let d = p - cell;
+        let t = fade(d);
+        let c = cell.as_ivec2();
+        let v = |x, y| {
+            let corner = IVec2::new(x, y);
+            gradient(hash(self.seed, c + corner)).dot(d - corner.as_vec2())
+        };
+        let bottom = lerp(v(0, 0), v(1, 0), t.x);
+        let top = lerp(v(0, 1), v(1, 1), t.x);
+        lerp(bottom, top, t.y)
+    }
+}
+
+/// Simplex gradient noise, roughly in the range `-1.0..=1.0`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Simplex {
+    pub seed: u32,
+}
+
+impl Simplex {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+}
+
+impl Noise for Simplex {
+    fn sample(&self, p: Vec2) -> f32 {
+        let f2 = (3f32.sqrt() - 1.0) / 2.0;
+        let g2 = (3.0 - 3f32.sqrt()) / 6.0;
+
+        // Skew into the simplex grid to find which triangle contains p.
+        let cell = (p + (p.x + p.y) * f2).floor();
+        let origin = cell - (cell.x + cell.y) * g2;
+        let d0 = p - origin;
+        let step = if d0.x > d0.y { IVec2::X } else { IVec2::Y };
+        let d1 = d0 - step.as_vec2() + g2;
+        let d2 = d0 - 1.0 + 2.0 * g2;
+
+        let c = cell.as_ivec2();
+        let corner = |offset: IVec2, d: Vec2| {
+            let t = 0.5 - d.length_squared();
+            if t <= 0.0 {
+                return 0.0;
+            }
+            let t2 = t * t;
+            t2 * t2 * gradient(hash(self.seed, c + offset)).dot(d)
+        };
+        70.0 * (corner(IVec2::ZERO, d0) + corner(step, d1) + corner(IVec2::ONE, d2))
+    }
+}
+
+/// Worley (cellular) noise, the distance from a position to the nearest of a
+/// set of randomly scattered points, with one point in each integer cell.
+///
+/// Values are `0.0` at each point and rarely exceed `1.0`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Worley {
+    pub seed: u32,
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    /// The position of the point in the given cell.
+    pub fn point(&self, cell: IVec2) -> Vec2 {
+        let h = hash(self.seed, cell);
+        let offset = Vec2::new((h & 0xffff) as f32, (h >> 16) as f32) / 65536.0;
+        cell.as_vec2() + offset
+    }
+}
+
+impl Noise for Worley {
+    fn sample(&self, p: Vec2) -> f32 {
+        let cell = p.floor().as_ivec2();
+        let mut nearest = f32::INFINITY;
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let point = self.point(cell + IVec2::new(x, y));
+                nearest = nearest.min(point.distance_squared(p));
+            }
+        }
+        nearest.sqrt()
+    }
+}
+
+/// How the octaves of a [Fractal] are combined.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FractalKind {
+    /// Fractal brownian motion, the sum of each octave.
+    #[default]
+    Fbm,
+    /// Sharp ridges where the noise crosses zero.
+    Ridged,
+    /// Rounded bumps from the absolute value of each octave.
+    Billow,
+}
+
+/// Layers multiple octaves of a noise function at increasing frequencies and
+/// decreasing amplitudes.
+///
+/// For noise in the range `-1.0..=1.0` the result is in the same range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fractal<N> {
+    pub noise: N,
+    pub kind: FractalKind,
+    /// The number of layers of noise.
+    pub octaves: u32,
+    /// The frequency multiplier for each octave.
+    pub lacunarity: f32,
+    /// The amplitude multiplier for each octave.
+    pub gain: f32,
+}
+
+impl<N: Noise> Fractal<N> {
+    pub fn new(noise: N, kind: FractalKind) -> Self {
+        Self {
+            noise,
+            kind,
+            octaves: 4,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    /// Fractal brownian motion.
+    pub fn fbm(noise: N) -> Self {
+        Self::new(noise, FractalKind::Fbm)
+    }
+
+    /// Ridged noise.
+    pub fn ridged(noise: N) -> Self {
+        Self::new(noise, FractalKind::Ridged)
+    }
+
+    /// Billow noise.
+    pub fn billow(noise: N) -> Self {
+        Self::new(noise, FractalKind::Billow)
+    }
+
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+}
+
+impl<N: Noise> Noise for Fractal<N> {
+    fn sample(&self, p: Vec2) -> f32 {
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for octave in 0..self.octaves {
+            // Offset each octave so lattice points don't line up.
+            let shift = Vec2::new(19.19, 7.13) * octave as f32;
+            let n = self.noise.sample(p * frequency + shift);
+            let n = match self.kind {
+                FractalKind::Fbm => n,
+                FractalKind::Ridged => {
+                    let r = 1.0 - n.abs();
+                    r * r * 2.0 - 1.0
+                }
+                FractalKind::Billow => n.abs() * 2.0 - 1.0,
+            };
+            sum += n * amplitude;
+            total += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if total > 0.0 {
+            sum / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Hash a seed and a position into a random value.
+#[inline]
+fn hash(seed: u32, p: IVec2) -> u32 {
+    let mix = |mut h: u32| {
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^ (h >> 16)
+    };
+    let h = mix(seed.wrapping_add(0x9e37_79b9) ^ p.x as u32);
+    mix(h.wrapping_add(0x9e37_79b9) ^ p.y as u32)
+}
+
+/// Map a hash to the range `-1.0..=1.0`.
+#[inline]
+fn unit(h: u32) -> f32 {
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Pick one of eight gradient directions from a hash.
+#[inline]
+fn gradient(h: u32) -> Vec2 {
+    const GRADIENTS: [Vec2; 8] = [
+        Vec2::new(1.0, 1.0),
+        Vec2::new(-1.0, 1.0),
+        Vec2::new(1.0, -1.0),
+        Vec2::new(-1.0, -1.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(-1.0, 0.0),
+        Vec2::new(0.0, 1.0),
+        Vec2::new(0.0, -1.0),
+    ];
+    GRADIENTS[(h >> 29) as usize]
+}
+
+/// Quintic smoothing curve for interpolation.
+#[inline]
+fn fade(t: Vec2) -> Vec2 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> impl Iterator<Item = Vec2> {
+        (0..2000).map(|i| Vec2::new(i as f32 * 0.37 - 300.0, (i % 97) as f32 * 1.13 - 50.0))
+    }
+
+    #[test]
+    fn seeded() {
+        let p = Vec2::new(3.7, -12.2);
+        assert_eq!(Perlin::new(1).sample(p), Perlin::new(1).sample(p));
+        assert_ne!(Perlin::new(1).sample(p), Perlin::new(2).sample(p));
+        assert_ne!(Simplex::new(1).sample(p), Simplex::new(2).sample(p));
+        assert_ne!(ValueNoise::new(1).sample(p), ValueNoise::new(2).sample(p));
+        assert_ne!(Worley::new(1).sample(p), Worley::new(2).sample(p));
+    }
+
+    #[test]
+    fn ranges() {
+        let value = ValueNoise::new(3);
+        let perlin = Perlin::new(3);
+        let simplex = Simplex::new(3);
+        let fbm = Fractal::fbm(Simplex::new(3));
+        let ridged = Fractal::ridged(Perlin::new(3));
+        let billow = Fractal::billow(ValueNoise::new(3));
+        let bounded: [&dyn Noise; 6] = [&value, &perlin, &simplex, &fbm, &ridged, &billow];
+        for noise in bounded {
+            let mut min = f32::INFINITY;
+            let mut max = f32::NEG_INFINITY;
+            for p in samples() {
+                let v = noise.sample(p);
+                min = min.min(v);
+                max = max.max(v);
+            }
+            assert!(min >= -1.01 && max <= 1.01, "{min} {max}");
+            assert!(max - min > 0.5);
+        }
+
+        let worley = Worley::new(3);
+        assert!(samples().all(|p| (0.0..1.5).contains(&worley.sample(p))));
+        let point = worley.point(IVec2::new(5, -2));
+        assert!(worley.sample(point) < 1e-6);
+    }
+
+    #[test]
+    fn fill_chunks() {
+        let noise = Fractal::ridged(Simplex::new(9));
+        let mut whole = FloatGrid::new([20, 10]);
+        fill(&mut whole, &noise, [-10, 5], 0.1);
+
+        let mut chunk = FloatGrid::new([10, 10]);
+        fill(&mut chunk, &noise, [0, 5], 0.1);
+        for (p, v) in chunk.iter_xy() {
+            assert_eq!(whole.value(p + IVec2::new(10, 0)), v);
+        }
+
+        let mut partial = FloatGrid::new([10, 10]);
+        partial.set_all(5.0);
+        fill_rect(
+            &mut partial,
+            GridRect::new([2, 2], [3, 3]),
+            &noise,
+            [0, 5],
+            0.1,
+        );
+        assert_eq!(5.0, partial.value([1, 1]));
+        assert_eq!(chunk.value([3, 3]), partial.value([3, 3]));
+    }
+
+    #[test]
+    fn fill_rect_clipped() {
+        let noise = |p: Vec2| p.x + 1.0;
+        let mut grid = FloatGrid::new([4, 4]);
+        fill_rect(
+            &mut grid,
+            GridRect::new([2, 0], [4, 1]),
+            &noise,
+            [0, 0],
+            1.0,
+        );
+        assert_eq!([0.0, 0.0, 3.0, 4.0], grid.values()[..4]);
+        assert!(grid.values()[4..].iter().all(|v| *v == 0.0));
+
+        fill_rect(
+            &mut grid,
+            GridRect::new([-3, -3], [2, 2]),
+            &noise,
+            [0, 0],
+            1.0,
+        );
+        fill_rect(
+            &mut grid,
+            GridRect::new([-1, 1], [2, 1]),
+            &noise,
+            [0, 0],
+            1.0,
+        );
+        assert_eq!(1.0, grid.value([0, 1]));
+        assert_eq!(1, grid.values()[4..].iter().filter(|v| **v != 0.0).count());
+
+        let mut empty = FloatGrid::new([0, 0]);
+        fill_rect(
+            &mut empty,
+            GridRect::new([-1, -1], [3, 3]),
+            &noise,
+            [0, 0],
+            1.0,
+        );
+    }
+}
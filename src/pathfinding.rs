@@ -0,0 +1,268 @@
+//! A* and Dijkstra pathfinding on a 2d grid.
+//!
+//! Both searches work on any [SizedGrid] and take a cost function which
+//! returns the cost of moving into a given grid position, or [None] if the
+//! position can't be entered. The [walkable] and [cost_field] functions can be
+//! used to create a cost function from a [BitGrid] or [FloatGrid].
+//!
+//! The type of movement is chosen via an [Adjacency], either [Dir4]
+//! (orthogonal movement) or [Dir8] (orthogonal and diagonal movement).
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{direction::Dir4, pathfinding, BitGrid};
+//!
+//! let mut walkable = BitGrid::new([10, 10]).with_value(true);
+//! for y in 0..9 {
+//!     walkable.set_false([5, y]);
+//! }
+//!
+//! let (path, cost) =
+//!     pathfinding::astar::<Dir4>(&walkable, [0, 0], [9, 0], pathfinding::walkable(&walkable))
+//!         .unwrap();
+//!
+//! assert_eq!([0, 0], path[0].to_array());
+//! assert_eq!([9, 0], path[path.len() - 1].to_array());
+//! assert_eq!(27.0, cost);
+//! ```
+//!
+//! [Dir4]: crate::direction::Dir4
+//! [Dir8]: crate::direction::Dir8
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use glam::IVec2;
+
+use crate::{direction::Adjacency, BitGrid, FloatGrid, GridPoint, GridRead, SizedGrid};
+
+/// Find the shortest path between two points using the
+/// [A* search algorithm](https://en.wikipedia.org/wiki/A*_search_algorithm).
+///
+/// `cost` should return the cost of moving into the given position, or [None]
+/// if the position can't be entered. The heuristic is the
+/// [Adjacency::distance] between two points, so for the resulting path to be
+/// optimal every move should cost at least `1.0`.
+///
+/// Returns the path from `start` to `goal` (both included) along with the
+/// total cost of the path, or [None] if no path could be found.
+pub fn astar<D: Adjacency>(
+    grid: &impl SizedGrid,
+    start: impl GridPoint,
+    goal: impl GridPoint,
+    cost: impl Fn(IVec2) -> Option<f32>,
+) -> Option<(Vec<IVec2>, f32)> {
+    let goal = goal.to_ivec2();
+    search::<D>(grid, start.to_ivec2(), goal, cost, |p| {
+        D::distance(p, goal) as f32
+    })
+}
+
+/// Find the shortest path between two points using
+/// [Dijkstra's algorithm](https://en.wikipedia.org/wiki/Dijkstra%27s_algorithm).
+///
+/// `cost` should return the cost of moving into the given position, or [None]
+/// if the position can't be entered. Unlike [astar] the path will be optimal
+/// for any non-negative move cost.
+///
+/// Returns the path from `start` to `goal` (both included) along with the
+/// total cost of the path, or [None] if no path could be found.
+pub fn dijkstra<D: Adjacency>(
+    grid: &impl SizedGrid,
+    start: impl GridPoint,
+    goal: impl GridPoint,
+    cost: impl Fn(IVec2) -> Option<f32>,
+) -> Option<(Vec<IVec2>, f32)> {
+    search::<D>(grid, start.to_ivec2(), goal.to_ivec2(), cost, |_| 0.0)
+}
+
+/// A cost function where set bits of the [BitGrid] are walkable, with every
+/// move costing `1.0`.
+pub fn walkable(grid: &BitGrid) -> impl Fn(IVec2) -> Option<f32> + '_ {
+    move |p| grid.get(p).then_some(1.0)
+}
+
+/// A cost function where the values of the [FloatGrid] are the cost of moving
+/// into each position. Negative or non-finite values can't be entered.
+pub fn cost_field(grid: &FloatGrid) -> impl Fn(IVec2) -> Option<f32> + '_ {
+    move |p| {
+        let v = grid.value(p);
+        (v.is_finite() && v >= 0.0).then_some(v)
+    }
+}
+
+/// A cost function for any [GridRead] where values matching the predicate are
+/// walkable, with every move costing `1.0`.
+pub fn passable<'a, G: GridRead>(
+    grid: &'a G,
+    predicate: impl Fn(&G::Value) -> bool + 'a,
+) -> impl Fn(IVec2) -> Option<f32> + 'a {
+    move |p| grid.read(p).is_some_and(&predicate).then_some(1.0)
+}
+
+fn search<D: Adjacency>(
+    grid: &impl SizedGrid,
+    start: IVec2,
+    goal: IVec2,
+    cost: impl Fn(IVec2) -> Option<f32>,
+    heuristic: impl Fn(IVec2) -> f32,
+) -> Option<(Vec<IVec2>, f32)> {
+    let start_i = grid.try_transform_lti(start)?;
+    let goal_i = grid.try_transform_lti(goal)?;
+
+    let mut costs = vec![f32::INFINITY; grid.tile_count()];
+    let mut came_from = vec![usize::MAX; grid.tile_count()];
+    let mut open = BinaryHeap::new();
+
+    costs[start_i] = 0.0;
+    open.push(Node::new(start_i, 0.0, heuristic(start)));
+
+    while let Some(node) = open.pop() {
+        if node.index == goal_i {
+            let mut path = vec![goal];
+            let mut i = goal_i;
+            while i != start_i {
+                i = came_from[i];
+                path.push(grid.transform_itl(i));
+            }
+            path.reverse();
+            return Some((path, node.cost));
+        }
+        // A cheaper route to this node was already processed.
+        if node.cost > costs[node.index] {
+            continue;
+        }
+
+        let p = grid.transform_itl(node.index);
+        for dir in D::directions() {
+            let next = p + *dir;
+            let Some(next_i) = grid.try_transform_lti(next) else {
+                continue;
+            };
+            let Some(step) = cost(next) else {
+                continue;
+            };
+            let next_cost = node.cost + step;
+            if next_cost < costs[next_i] {
+                costs[next_i] = next_cost;
+                came_from[next_i] = node.index;
+                open.push(Node::new(next_i, next_cost, next_cost + heuristic(next)));
+            }
+        }
+    }
+
+    None
+}
+
+/// A node in the open set of a search, ordered so the lowest priority is
+/// popped first from a [BinaryHeap].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Node {
+    pub index: usize,
+    pub cost: f32,
+    pub priority: f32,
+}
+
+impl Node {
+    pub fn new(index: usize, cost: f32, priority: f32) -> Self {
+        Self {
+            index,
+            cost,
+            priority,
+        }
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::{Dir4, Dir8};
+
+    #[test]
+    fn straight_line() {
+        let grid = BitGrid::new([10, 10]).with_value(true);
+        let (path, cost) = astar::<Dir4>(&grid, [0, 0], [5, 0], walkable(&grid)).unwrap();
+
+        assert_eq!(6, path.len());
+        assert_eq!(5.0, cost);
+        for (i, p) in path.iter().enumerate() {
+            assert_eq!([i as i32, 0], p.to_array());
+        }
+    }
+
+    #[test]
+    fn diagonal() {
+        let grid = BitGrid::new([10, 10]).with_value(true);
+
+        let (path, cost) = astar::<Dir8>(&grid, [0, 0], [5, 5], walkable(&grid)).unwrap();
+        assert_eq!(6, path.len());
+        assert_eq!(5.0, cost);
+
+        let (path, cost) = astar::<Dir4>(&grid, [0, 0], [5, 5], walkable(&grid)).unwrap();
+        assert_eq!(11, path.len());
+        assert_eq!(10.0, cost);
+    }
+
+    #[test]
+    fn blocked() {
+        let mut grid = BitGrid::new([10, 10]).with_value(true);
+        for y in 0..10 {
+            grid.set_false([5, y]);
+        }
+
+        assert!(astar::<Dir8>(&grid, [0, 0], [9, 9], walkable(&grid)).is_none());
+        assert!(dijkstra::<Dir8>(&grid, [0, 0], [9, 9], walkable(&grid)).is_none());
+    }
+
+    #[test]
+    fn passable_grid() {
+        let grid: crate::Grid<char> = "
+.#.
+.#.
+...
+"
+        .parse()
+        .unwrap();
+        let (path, cost) =
+            astar::<Dir4>(&grid, [0, 2], [2, 2], passable(&grid, |c| *c == '.')).unwrap();
+        assert_eq!(6.0, cost);
+        assert_eq!(7, path.len());
+    }
+
+    #[test]
+    fn cost_field_detour() {
+        let mut costs = FloatGrid::new([5, 3]);
+        costs.set_all(1.0);
+        for x in 1..4 {
+            costs.set_value([x, 1], 10.0);
+        }
+        costs.set_value([2, 2], f32::INFINITY);
+
+        let (path, cost) = dijkstra::<Dir4>(&costs, [0, 1], [4, 1], cost_field(&costs)).unwrap();
+        assert_eq!(6.0, cost);
+        assert!(path.iter().all(|p| p.y != 1 || p.x == 0 || p.x == 4));
+
+        let astar_result = astar::<Dir4>(&costs, [0, 1], [4, 1], cost_field(&costs)).unwrap();
+        assert_eq!(cost, astar_result.1);
+    }
+}
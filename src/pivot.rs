@@ -12,6 +12,7 @@ use super::GridSize;
 /// pivot. Each pivot has it's own coordinate space it uses to calculate
 /// the final adjusted position.
 #[derive(Eq, PartialEq, Clone, Copy, Debug, Ordinalize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pivot {
     /// Coordinate space: X increases to the right, Y increases downwards.
     TopLeft,
@@ -85,6 +86,7 @@ impl Pivot {
 
 /// A grid point that may optionally have a pivot applied to it.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PivotedPoint {
     pub point: IVec2,
     pub pivot: Option<Pivot>,
@@ -0,0 +1,318 @@
+//! Flood fills and connected-component labeling for finding rooms, islands
+//! and other connected regions on a grid.
+//!
+//! Connectivity is chosen with the [Adjacency] type parameter: [Dir4] for
+//! four-way and [Dir8] for eight-way connected regions.
+//!
+//! [Dir4]: crate::direction::Dir4
+//! [Dir8]: crate::direction::Dir8
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{direction::{Dir4, Dir8}, region, Grid};
+//!
+//! let grid: Grid<char> = "
+//! #..
+//! .#.
+//! ..#
+//! ".parse().unwrap();
+//!
+//! assert_eq!(3, region::flood_fill::<Dir4, _>(&grid, [0, 0]).len());
+//! assert_eq!(6, region::flood_fill::<Dir8, _>(&grid, [0, 0]).len());
+//!
+//! let walls = region::label_with::<Dir4, _>(&grid, |c| *c == '#');
+//! assert_eq!(3, walls.components.len());
+//! assert_eq!(0, walls.label_at([1, 0]));
+//! ```
+
+use glam::{IVec2, Vec2};
+
+use crate::{direction::Adjacency, BitGrid, Grid, GridPoint, GridRect, GridSize, SizedGrid};
+
+/// Find all positions connected to `start` that have the same value as
+/// `start`.
+///
+/// Returns an empty list if `start` is out of bounds.
+pub fn flood_fill<D: Adjacency, T: PartialEq>(grid: &Grid<T>, start: impl GridPoint) -> Vec<IVec2> {
+    let Some(value) = grid.get(start) else {
+        return Vec::new();
+    };
+    flood_fill_with::<D, T>(grid, start, |v| v == value)
+}
+
+/// Find all positions connected to `start` where `predicate` returns true.
+///
+/// Returns an empty list if `start` is out of bounds or doesn't match the
+/// predicate.
+pub fn flood_fill_with<D: Adjacency, T>(
+    grid: &Grid<T>,
+    start: impl GridPoint,
+    predicate: impl Fn(&T) -> bool,
+) -> Vec<IVec2> {
+    let mut points = Vec::new();
+    fill::<D>(
+        grid.size(),
+        start.to_ivec2(),
+        |p| grid.get(p).is_some_and(&predicate),
+        |p| points.push(p),
+    );
+    points
+}
+
+/// Find all positions connected to `start` where `predicate` returns true and
+/// set them to true in `filled`. Other bits in `filled` are left unchanged.
+pub fn flood_fill_into<D: Adjacency, T>(
+    grid: &Grid<T>,
+    start: impl GridPoint,
+    predicate: impl Fn(&T) -> bool,
+    filled: &mut BitGrid,
+) {
+    assert_eq!(
+        grid.size(),
+        filled.size(),
+        "Attempting to flood fill into a BitGrid of a different size"
+    );
+    fill::<D>(
+        grid.size(),
+        start.to_ivec2(),
+        |p| grid.get(p).is_some_and(&predicate),
+        |p| filled.set_true(p),
+    );
+}
+
+/// Find all positions connected to `start` that have the same bit value as
+/// `start`.
+///
+/// Returns an empty list if `start` is out of bounds.
+pub fn flood_fill_bits<D: Adjacency>(grid: &BitGrid, start: impl GridPoint) -> Vec<IVec2> {
+    if !grid.in_bounds(start) {
+        return Vec::new();
+    }
+    let value = grid.get(start);
+    let mut points = Vec::new();
+    fill::<D>(
+        grid.size(),
+        start.to_ivec2(),
+        |p| grid.get(p) == value,
+        |p| points.push(p),
+    );
+    points
+}
+
+/// A connected region found by labeling.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Component {
+    /// The label of the component in [Labels::grid].
+    pub label: u32,
+    /// The number of positions in the component.
+    pub size: usize,
+    /// The smallest rect containing every position in the component.
+    pub bounds: GridRect,
+    /// The average of every position in the component.
+    pub centroid: Vec2,
+}
+
+/// The result of connected-component labeling.
+///
+/// Each component is given a label starting from 1, positions that aren't part
+/// of any component are labeled 0.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Labels {
+    /// The label of each position.
+    pub grid: Grid<u32>,
+    /// Every component, ordered by label.
+    pub components: Vec<Component>,
+}
+
+impl Labels {
+    /// The label at the given position.
+    pub fn label_at(&self, xy: impl GridPoint) -> u32 {
+        self.grid.get(xy).copied().unwrap_or(0)
+    }
+
+    /// Retrieve a component by it's label.
+    ///
+    /// Returns [None] for label 0 or a label that doesn't exist.
+    pub fn component(&self, label: u32) -> Option<&Component> {
+        self.components.get((label as usize).checked_sub(1)?)
+    }
+
+    /// Retrieve the component containing the given position.
+    pub fn component_at(&self, xy: impl GridPoint) -> Option<&Component> {
+        self.component(self.label_at(xy))
+    }
+}
+
+/// Label connected regions of equal values. Every position is part of a
+/// component.
+pub fn label<D: Adjacency, T: PartialEq>(grid: &Grid<T>) -> Labels {
+    let value = |p: IVec2| grid.get(p).unwrap();
+    label_by::<D>(grid.size(), |_| true, |a, b| value(a) == value(b))
+}
+
+/// Label connected regions of positions where `predicate` returns true. Other
+/// positions are labeled 0.
+pub fn label_with<D: Adjacency, T>(grid: &Grid<T>, predicate: impl Fn(&T) -> bool) -> Labels {
+    label_by::<D>(
+        grid.size(),
+        |p| grid.get(p).is_some_and(&predicate),
+        |_, _| true,
+    )
+}
+
+/// Label connected regions of set bits. Unset bits are labeled 0.
+pub fn label_bits<D: Adjacency>(grid: &BitGrid) -> Labels {
+    label_by::<D>(grid.size(), |p| grid.get(p), |_, _| true)
+}
+
+/// Visit every in bounds position connected to `start` where `inside` returns
+/// true.
+fn fill<D: Adjacency>(
+    size: impl GridSize,
+    start: IVec2,
+    inside: impl Fn(IVec2) -> bool,
+    mut visit: impl FnMut(IVec2),
+) {
+    let mut seen = BitGrid::new(size);
+    if !seen.in_bounds(start) || !inside(start) {
+        return;
+    }
+    seen.set_true(start);
+    let mut stack = vec![start];
+    while let Some(p) = stack.pop() {
+        visit(p);
+        for dir in D::directions() {
+            let next = p + *dir;
+            if seen.in_bounds(next) && !seen.get(next) && inside(next) {
+                seen.set_true(next);
+                stack.push(next);
+            }
+        }
+    }
+}
+
+/// Label every region of positions where `inside` returns true, where
+/// `connected` decides if two adjacent positions are in the same region.
+fn label_by<D: Adjacency>(
+    size: impl GridSize,
+    inside: impl Fn(IVec2) -> bool,
+    connected: impl Fn(IVec2, IVec2) -> bool,
+) -> Labels {
+    let mut grid: Grid<u32> = Grid::new(size.to_uvec2());
+    let mut components = Vec::new();
+    let mut stack = Vec::new();
+    for start in grid.bounds().iter_points() {
+        if grid[start] != 0 || !inside(start) {
+            continue;
+        }
+        let label = components.len() as u32 + 1;
+        let (mut min, mut max) = (start, start);
+        let mut sum = [0i64; 2];
+        let mut count = 0;
+
+        grid[start] = label;
+        stack.push(start);
+        while let Some(p) = stack.pop() {
+            count += 1;
+            min = min.min(p);
+            max = max.max(p);
+            sum[0] += p.x as i64;
+            sum[1] += p.y as i64;
+            for dir in D::directions() {
+                let next = p + *dir;
+                if grid.get(next) == Some(&0) && inside(next) && connected(p, next) {
+                    grid[next] = label;
+                    stack.push(next);
+                }
+            }
+        }
+        components.push(Component {
+            label,
+            size: count,
+            bounds: GridRect::from_points(min, max),
+            centroid: Vec2::new(sum[0] as f32, sum[1] as f32) / count as f32,
+        });
+    }
+    Labels { grid, components }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::{Dir4, Dir8};
+
+    fn map() -> Grid<char> {
+        "
+#..#.
+.#..#
+...#.
+##.#.
+"
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn fill() {
+        let grid = map();
+        let floor = flood_fill::<Dir4, _>(&grid, [0, 1]);
+        assert_eq!(9, floor.len());
+        assert!(floor.iter().all(|p| grid[*p] == '.'));
+
+        assert_eq!(2, flood_fill::<Dir4, _>(&grid, [4, 0]).len());
+        assert_eq!(12, flood_fill::<Dir8, _>(&grid, [4, 0]).len());
+        assert!(flood_fill_with::<Dir4, _>(&grid, [0, 0], |c| *c == '.').is_empty());
+        assert!(flood_fill::<Dir4, _>(&grid, [5, 0]).is_empty());
+
+        let mut filled = BitGrid::new(grid.size());
+        flood_fill_into::<Dir8, _>(&grid, [3, 0], |c| *c == '#', &mut filled);
+        assert_eq!(4, filled.iter().filter(|b| *b).count());
+        assert!(filled.get([4, 2]));
+    }
+
+    #[test]
+    fn fill_bits() {
+        let mut bits = BitGrid::new([4, 4]);
+        bits.set_true([1, 1]);
+        bits.set_true([2, 2]);
+        assert_eq!(1, flood_fill_bits::<Dir4>(&bits, [1, 1]).len());
+        assert_eq!(2, flood_fill_bits::<Dir8>(&bits, [1, 1]).len());
+        assert_eq!(14, flood_fill_bits::<Dir4>(&bits, [0, 0]).len());
+    }
+
+    #[test]
+    fn labels() {
+        let grid = map();
+        let walls = label_with::<Dir4, _>(&grid, |c| *c == '#');
+        assert_eq!(6, walls.components.len());
+        assert_eq!(0, walls.label_at([2, 0]));
+
+        let walls = label_with::<Dir8, _>(&grid, |c| *c == '#');
+        assert_eq!(3, walls.components.len());
+        let right = walls.component_at([3, 0]).unwrap();
+        assert_eq!(4, right.size);
+        assert_eq!(GridRect::from_points([3, 0], [4, 3]), right.bounds);
+        assert_eq!(Vec2::new(3.25, 1.5), right.centroid);
+        assert_eq!(right.label, walls.label_at([3, 3]));
+
+        let all = label::<Dir4, _>(&grid);
+        assert_eq!(9, all.components.len());
+        assert_eq!(20, all.components.iter().map(|c| c.size).sum::<usize>());
+        assert_eq!(None, all.component(0));
+    }
+
+    #[test]
+    fn labels_bits() {
+        let mut bits = BitGrid::new([5, 5]);
+        bits.set_true([0, 0]);
+        bits.set_true([1, 1]);
+        bits.set_true([4, 4]);
+        assert_eq!(3, label_bits::<Dir4>(&bits).components.len());
+        let labels = label_bits::<Dir8>(&bits);
+        assert_eq!(2, labels.components.len());
+        assert_eq!(labels.label_at([0, 0]), labels.label_at([1, 1]));
+    }
+}
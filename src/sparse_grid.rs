@@ -10,11 +10,52 @@ use glam::{IVec2, UVec2};
 use crate::{GridPoint, SizedGrid};
 
 /// A simple data structure for storing values in a sparse 2d grid.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SparseGridData<T>"))]
 pub struct SparseGrid<T> {
     data: BTreeMap<Cell, T>,
     size: UVec2,
 }
 
+/// Unvalidated grid data, with each value stored alongside it's position. Used
+/// to check that every position is unique when a [SparseGrid] is deserialized.
+///
+/// Positions outside the grid's size are accepted, since [SparseGrid::insert]
+/// allows them and any saved grid must be able to be loaded again.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SparseGridData<T> {
+    data: Vec<(IVec2, T)>,
+    size: UVec2,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<SparseGridData<T>> for SparseGrid<T> {
+    type Error = String;
+
+    fn try_from(grid: SparseGridData<T>) -> Result<Self, Self::Error> {
+        let SparseGridData { data, size } = grid;
+        let mut out = Self::new(size);
+        for (p, v) in data {
+            if out.data.insert(Cell(p), v).is_some() {
+                return Err(format!("Grid position {} appears more than once", p));
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SparseGrid<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("SparseGrid", 2)?;
+        state.serialize_field("data", &self.iter().collect::<Vec<_>>())?;
+        state.serialize_field("size", &self.size)?;
+        state.end()
+    }
+}
+
 impl<T> SparseGrid<T> {
     pub fn new(size: UVec2) -> Self {
         Self {
@@ -86,3 +127,49 @@ where
         self.data.entry(Cell(index.to_ivec2())).or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_order() {
+        let mut grid = SparseGrid::new(UVec2::new(4, 3));
+        grid.insert([3, 0], 1);
+        grid.insert([0, 2], 2);
+        grid[[1, 0]] += 3;
+        assert_eq!(Some(&3), grid.get([1, 0]));
+        assert_eq!(
+            vec![[1, 0], [3, 0], [0, 2]],
+            grid.iter().map(|(p, _)| p.to_array()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut grid = SparseGrid::new(UVec2::new(4, 3));
+        grid.insert([1, 2], 5);
+        grid.insert([3, 0], 7);
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: SparseGrid<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(grid.size(), loaded.size());
+        assert!(grid.iter().eq(loaded.iter()));
+
+        // Insert doesn't check bounds, so out of bounds values must survive a
+        // save and reload.
+        let mut grid = SparseGrid::new(UVec2::new(2, 2));
+        grid.insert([5, 5], 1);
+        grid.insert([-1, 0], 2);
+        let json = serde_json::to_string(&grid).unwrap();
+        let loaded: SparseGrid<i32> = serde_json::from_str(&json).unwrap();
+        assert!(grid.iter().eq(loaded.iter()));
+        assert_eq!(Some(&1), loaded.get([5, 5]));
+
+        let duplicate = r#"{"data":[[[1,2],5],[[1,2],7]],"size":[4,3]}"#;
+        let err = serde_json::from_str::<SparseGrid<i32>>(duplicate)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("more than once"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Traits for reading and writing values on any kind of grid, so algorithms
+//! can be written once and used with every grid type.
+//!
+//! [GridRead] and [GridWrite] are implemented for [Grid], [BitGrid],
+//! [FloatGrid], [SparseGrid] and grid views, and can be implemented for user
+//! defined grids.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{storage, BitGrid, FloatGrid, GridRead, GridRect};
+//!
+//! fn count<G: GridRead>(grid: &G, predicate: impl Fn(&G::Value) -> bool) -> usize {
+//!     storage::find_all(grid, predicate).count()
+//! }
+//!
+//! let mut bits = BitGrid::new([5, 5]);
+//! let mut floats = FloatGrid::new([5, 5]);
+//! storage::fill(&mut bits, GridRect::new([1, 1], [2, 2]), true);
+//! storage::fill(&mut floats, GridRect::new([3, 3], [5, 5]), 1.5);
+//!
+//! assert_eq!(4, count(&bits, |b| *b));
+//! assert_eq!(4, count(&floats, |v| *v > 1.0));
+//! assert_eq!(Some([3, 3].into()), storage::find(&floats, |v| *v > 1.0));
+//! ```
+
+use glam::IVec2;
+
+use crate::{
+    grid::{clip_blit, GridView, GridViewMut},
+    sparse_grid::SparseGrid,
+    BitGrid, FloatGrid, Grid, GridPoint, GridRect, SizedGrid,
+};
+
+/// A grid which values can be read from.
+pub trait GridRead: SizedGrid {
+    /// The type of value stored in the grid.
+    type Value;
+
+    /// Retrieve the value at the given position.
+    ///
+    /// Returns [None] if the position is out of bounds or has no value.
+    fn read(&self, xy: impl GridPoint) -> Option<&Self::Value>;
+}
+
+/// A grid which values can be written to.
+pub trait GridWrite: GridRead {
+    /// Set the value at the given position.
+    ///
+    /// Returns false and leaves the grid unchanged if the position is out of
+    /// bounds.
+    fn write(&mut self, xy: impl GridPoint, value: Self::Value) -> bool;
+}
+
+/// Set every position in the rect to `value`. Positions outside the grid are
+/// ignored.
+pub fn fill<G: GridWrite>(grid: &mut G, rect: GridRect, value: G::Value)
+where
+    G::Value: Clone,
+{
+    for p in rect.iter_points() {
+        grid.write(p, value.clone());
+    }
+}
+
+/// Copy a rectangular area of one grid into another, with the bottom left of
+/// the area placed at `dest_pos`.
+///
+/// The area is clipped to the bounds of both grids, and positions without a
+/// value in `src` are skipped.
+pub fn copy<S, D>(src: &S, src_rect: GridRect, dest: &mut D, dest_pos: impl GridPoint)
+where
+    S: GridRead,
+    D: GridWrite<Value = S::Value>,
+    S::Value: Clone,
+{
+    let Some((rect, offset)) = clip_blit(src.size(), src_rect, dest.size(), dest_pos) else {
+        return;
+    };
+    for p in rect.iter_points() {
+        if let Some(v) = src.read(p) {
+            dest.write(p + offset, v.clone());
+        }
+    }
+}
+
+/// Find the first position, from the bottom left to the top right, with a value
+/// matching the predicate.
+pub fn find<G: GridRead>(grid: &G, predicate: impl Fn(&G::Value) -> bool) -> Option<IVec2> {
+    find_all(grid, predicate).next()
+}
+
+/// Iterate over every position, from the bottom left to the top right, with a
+/// value matching the predicate.
+pub fn find_all<'a, G: GridRead>(
+    grid: &'a G,
+    predicate: impl Fn(&G::Value) -> bool + 'a,
+) -> impl Iterator<Item = IVec2> + 'a {
+    grid.iter_grid_points()
+        .filter(move |p| grid.read(*p).is_some_and(&predicate))
+}
+
+impl<T> GridRead for Grid<T> {
+    type Value = T;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&T> {
+        self.get(xy)
+    }
+}
+
+impl<T> GridWrite for Grid<T> {
+    fn write(&mut self, xy: impl GridPoint, value: T) -> bool {
+        self.get_mut(xy).map(|v| *v = value).is_some()
+    }
+}
+
+impl GridRead for BitGrid {
+    type Value = bool;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&bool> {
+        self.in_bounds(xy)
+            .then(|| &self.bits()[self.transform_lti(xy)])
+    }
+}
+
+impl GridWrite for BitGrid {
+    fn write(&mut self, xy: impl GridPoint, value: bool) -> bool {
+        let in_bounds = self.in_bounds(xy);
+        if in_bounds {
+            self.set(xy, value);
+        }
+        in_bounds
+    }
+}
+
+impl GridRead for FloatGrid {
+    type Value = f32;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&f32> {
+        self.in_bounds(xy).then(|| &self[xy])
+    }
+}
+
+impl GridWrite for FloatGrid {
+    fn write(&mut self, xy: impl GridPoint, value: f32) -> bool {
+        self.get_value_mut(xy).map(|v| *v = value).is_some()
+    }
+}
+
+impl<T> GridRead for SparseGrid<T> {
+    type Value = T;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&T> {
+        self.in_bounds(xy).then(|| self.get(xy)).flatten()
+    }
+}
+
+impl<T> GridWrite for SparseGrid<T> {
+    fn write(&mut self, xy: impl GridPoint, value: T) -> bool {
+        let in_bounds = self.in_bounds(xy);
+        if in_bounds {
+            self.insert(xy, value);
+        }
+        in_bounds
+    }
+}
+
+impl<T> GridRead for GridView<'_, T> {
+    type Value = T;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&T> {
+        self.get(xy)
+    }
+}
+
+impl<T> GridRead for GridViewMut<'_, T> {
+    type Value = T;
+
+    fn read(&self, xy: impl GridPoint) -> Option<&T> {
+        self.get(xy)
+    }
+}
+
+impl<T> GridWrite for GridViewMut<'_, T> {
+    fn write(&mut self, xy: impl GridPoint, value: T) -> bool {
+        self.get_mut(xy).map(|v| *v = value).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec2;
+
+    use super::*;
+
+    /// A user defined grid that only stores a single row.
+    struct Row(Vec<u8>);
+
+    impl SizedGrid for Row {
+        fn size(&self) -> UVec2 {
+            UVec2::new(self.0.len() as u32, 1)
+        }
+    }
+
+    impl GridRead for Row {
+        type Value = u8;
+
+        fn read(&self, xy: impl GridPoint) -> Option<&u8> {
+            self.in_bounds(xy).then(|| &self.0[xy.x() as usize])
+        }
+    }
+
+    #[test]
+    fn read_write() {
+        let mut grid = Grid::filled(0, [3, 3]);
+        let mut sparse = SparseGrid::new(UVec2::new(3, 3));
+        let mut bits = BitGrid::new([3, 3]);
+        let mut floats = FloatGrid::new([3, 3]);
+
+        assert!(grid.write([1, 2], 5));
+        assert!(sparse.write([1, 2], 5));
+        assert!(bits.write([1, 2], true));
+        assert!(floats.write([1, 2], 5.0));
+        assert!(!grid.write([3, 0], 5));
+        assert!(!sparse.write([3, 0], 5));
+        assert!(!bits.write([0, -1], true));
+        assert!(!floats.write([0, 3], 5.0));
+
+        assert_eq!(Some(&5), grid.read([1, 2]));
+        assert_eq!(Some(&5), sparse.read([1, 2]));
+        assert_eq!(Some(&true), bits.read([1, 2]));
+        assert_eq!(Some(&5.0), floats.read([1, 2]));
+        assert_eq!(None, sparse.read([0, 0]));
+        assert_eq!(None, sparse.read([3, 0]));
+        assert_eq!(None, bits.read([3, 0]));
+    }
+
+    #[test]
+    fn generic() {
+        let mut sparse = SparseGrid::new(UVec2::new(4, 4));
+        fill(&mut sparse, GridRect::new([2, 2], [4, 4]), 'a');
+        assert_eq!(4, sparse.iter().count());
+
+        let mut grid = Grid::filled('.', [4, 4]);
+        copy(&sparse, GridRect::new([0, 0], [4, 4]), &mut grid, [-1, 0]);
+        assert_eq!(".aa.\n.aa.\n....\n....\n", grid.to_string());
+
+        let mut view = grid.view_mut(GridRect::new([1, 1], [2, 2]));
+        fill(&mut view, GridRect::new([0, 0], [1, 1]), 'b');
+        assert_eq!(Some(IVec2::new(1, 1)), find(&grid, |c| *c == 'b'));
+        assert_eq!(5, find_all(&grid, |c| *c != '.').count());
+
+        let row = Row(vec![1, 2, 3, 2]);
+        assert_eq!(
+            vec![IVec2::new(1, 0), IVec2::new(3, 0)],
+            find_all(&row, |v| *v == 2).collect::<Vec<_>>()
+        );
+    }
+}
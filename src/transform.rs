@@ -0,0 +1,405 @@
+//! Rotations, flips and transposes of grids, points and rects.
+//!
+//! A [GridTransform] is one of the eight symmetries of a square. Rotations are
+//! counter-clockwise, since the y axis points up.
+//!
+//! Transforms that swap the x and y axes can only be applied to a grid in
+//! place if the grid is square. The allocating versions work on grids of any
+//! size.
+//!
+//! # Example
+//!
+//! ```
+//! use sark_grids::{Grid, GridTransform};
+//!
+//! let grid: Grid<char> = "
+//! ab
+//! cd
+//! ".parse().unwrap();
+//!
+//! let rotated = grid.transformed(GridTransform::Rotate90);
+//! assert_eq!("bd\nac\n", rotated.to_string());
+//!
+//! let mut grid = grid;
+//! grid.transform(GridTransform::FlipX);
+//! assert_eq!("ba\ndc\n", grid.to_string());
+//! ```
+
+use glam::{IVec2, UVec2};
+
+use crate::{BitGrid, FloatGrid, Grid, GridPoint, GridRect, GridSize, SizedGrid};
+
+/// One of the eight ways to rotate or mirror a grid.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GridTransform {
+    /// Leave the grid unchanged.
+    #[default]
+    Identity,
+    /// Rotate 90 degrees counter-clockwise.
+    Rotate90,
+    /// Rotate 180 degrees.
+    Rotate180,
+    /// Rotate 270 degrees counter-clockwise, or 90 degrees clockwise.
+    Rotate270,
+    /// Mirror horizontally, so left becomes right.
+    FlipX,
+    /// Mirror vertically, so top becomes bottom.
+    FlipY,
+    /// Swap the x and y axes, mirroring across the bottom left to top right
+    /// diagonal.
+    Transpose,
+    /// Mirror across the top left to bottom right diagonal.
+    AntiTranspose,
+}
+
+impl GridTransform {
+    /// Every transform, useful for generating every symmetry of a pattern.
+    pub const ALL: [GridTransform; 8] = [
+        GridTransform::Identity,
+        GridTransform::Rotate90,
+        GridTransform::Rotate180,
+        GridTransform::Rotate270,
+        GridTransform::FlipX,
+        GridTransform::FlipY,
+        GridTransform::Transpose,
+        GridTransform::AntiTranspose,
+    ];
+
+    /// The transformed x and y axes.
+    fn axes(self) -> [IVec2; 2] {
+        match self {
+            GridTransform::Identity => [IVec2::X, IVec2::Y],
+            GridTransform::Rotate90 => [IVec2::Y, IVec2::NEG_X],
+            GridTransform::Rotate180 => [IVec2::NEG_X, IVec2::NEG_Y],
+            GridTransform::Rotate270 => [IVec2::NEG_Y, IVec2::X],
+            GridTransform::FlipX => [IVec2::NEG_X, IVec2::Y],
+            GridTransform::FlipY => [IVec2::X, IVec2::NEG_Y],
+            GridTransform::Transpose => [IVec2::Y, IVec2::X],
+            GridTransform::AntiTranspose => [IVec2::NEG_Y, IVec2::NEG_X],
+        }
+    }
+
+    /// Whether the transform swaps the width and height of a grid.
+    pub fn swaps_axes(self) -> bool {
+        self.axes()[0].x == 0
+    }
+
+    /// The transform that undoes this one.
+    pub fn inverse(self) -> GridTransform {
+        match self {
+            GridTransform::Rotate90 => GridTransform::Rotate270,
+            GridTransform::Rotate270 => GridTransform::Rotate90,
+            t => t,
+        }
+    }
+
+    /// The transform equivalent to applying this one followed by `other`.
+    pub fn then(self, other: GridTransform) -> GridTransform {
+        let axes = self.axes().map(|a| other.apply(a));
+        *Self::ALL.iter().find(|t| t.axes() == axes).unwrap()
+    }
+
+    /// Transform a point, such as the position of a
+    /// [GridShape](crate::GridShape), around world origin.
+    pub fn apply(self, xy: impl GridPoint) -> IVec2 {
+        let [x, y] = self.axes();
+        let xy = xy.to_ivec2();
+        x * xy.x + y * xy.y
+    }
+
+    /// Transform a rect around world origin.
+    ///
+    /// An empty rect stays empty.
+    pub fn apply_rect(self, rect: GridRect) -> GridRect {
+        // Transform the outer corners of the rect, which are well defined even
+        // for an empty rect, then shift from the transformed corner back to the
+        // tile that [GridTransform::apply] moves the bottom left tile to.
+        let a = self.apply(rect.pos);
+        let b = self.apply(rect.pos + rect.size.as_ivec2());
+        let tile = self.apply(IVec2::ONE).min(IVec2::ZERO);
+        GridRect::new(a.min(b) - tile, self.apply_size(rect.size))
+    }
+
+    /// The size of a grid after it's been transformed.
+    pub fn apply_size(self, size: impl GridSize) -> UVec2 {
+        let size = size.to_uvec2();
+        match self.swaps_axes() {
+            true => UVec2::new(size.y, size.x),
+            false => size,
+        }
+    }
+
+    /// Transform a position within a grid of the given size to it's position
+    /// in the transformed grid.
+    pub fn apply_in(self, xy: impl GridPoint, size: impl GridSize) -> IVec2 {
+        let far = self.apply(size.to_ivec2() - 1);
+        self.apply(xy) + IVec2::ZERO.max(-far)
+    }
+
+    /// Transform a rect within a grid of the given size to it's position in
+    /// the transformed grid.
+    ///
+    /// An empty rect stays empty.
+    pub fn apply_rect_in(self, rect: GridRect, size: impl GridSize) -> GridRect {
+        self.apply_rect(rect)
+            .translated(self.apply_in(IVec2::ZERO, size))
+    }
+
+    /// Assert that the transform can be applied in place to a grid of the
+    /// given size.
+    fn assert_in_place(self, size: UVec2) {
+        assert!(
+            !self.swaps_axes() || size.x == size.y,
+            "Attempting to apply {:?} in place to a non-square grid of size {}",
+            self,
+            size
+        );
+    }
+
+    /// For each index of the transformed grid, the index of the value it comes
+    /// from in a grid of the given size.
+    fn source_indices(self, size: UVec2) -> impl Iterator<Item = usize> {
+        let inverse = self.inverse();
+        let new_size = self.apply_size(size);
+        GridRect::new([0, 0], new_size).iter_points().map(move |q| {
+            let p = inverse.apply_in(q, new_size);
+            p.y as usize * size.x as usize + p.x as usize
+        })
+    }
+
+    /// Move every value of a grid to it's transformed position without
+    /// allocating, by following each cycle of the permutation and calling
+    /// `swap` with pairs of indices to exchange.
+    fn permute(self, size: UVec2, mut swap: impl FnMut(usize, usize)) {
+        self.assert_in_place(size);
+        let w = size.x as usize;
+        let dest = |i: usize| {
+            let p = IVec2::new((i % w) as i32, (i / w) as i32);
+            let q = self.apply_in(p, size);
+            q.y as usize * w + q.x as usize
+        };
+        // Every transform undoes itself in at most four steps, so cycles are
+        // short enough to walk from each index. A cycle is only moved from
+        // it's smallest index so it isn't moved twice.
+        for start in 0..w * size.y as usize {
+            let mut i = dest(start);
+            while i > start {
+                i = dest(i);
+            }
+            if i < start {
+                continue;
+            }
+            let mut i = dest(start);
+            while i != start {
+                swap(start, i);
+                i = dest(i);
+            }
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Rotate or mirror the grid in place.
+    ///
+    /// Panics if the transform swaps the axes and the grid isn't square.
+    pub fn transform(&mut self, transform: GridTransform) {
+        let size = self.size();
+        let data = self.slice_mut();
+        transform.permute(size, |a, b| data.swap(a, b));
+    }
+
+    /// Create a rotated or mirrored copy of the grid.
+    pub fn transformed(&self, transform: GridTransform) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let data = transform
+            .source_indices(self.size())
+            .map(|i| self.slice()[i].clone())
+            .collect();
+        Grid::from_parts(data, transform.apply_size(self.size()))
+    }
+}
+
+impl FloatGrid {
+    /// Rotate or mirror the grid in place.
+    ///
+    /// Panics if the transform swaps the axes and the grid isn't square.
+    pub fn transform(&mut self, transform: GridTransform) {
+        let size = self.size();
+        let values = self.values_mut();
+        transform.permute(size, |a, b| values.swap(a, b));
+    }
+
+    /// Create a rotated or mirrored copy of the grid.
+    pub fn transformed(&self, transform: GridTransform) -> FloatGrid {
+        let mut out = FloatGrid::new(transform.apply_size(self.size()));
+        let sources = transform.source_indices(self.size());
+        for (v, i) in out.values_mut().iter_mut().zip(sources) {
+            *v = self.values()[i];
+        }
+        out
+    }
+}
+
+impl BitGrid {
+    /// Rotate or mirror the grid in place.
+    ///
+    /// Panics if the transform swaps the axes and the grid isn't square.
+    pub fn transform(&mut self, transform: GridTransform) {
+        transform.permute(self.size(), |a, b| {
+            let (bit_a, bit_b) = (self.get_index(a), self.get_index(b));
+            self.set_index(a, bit_b);
+            self.set_index(b, bit_a);
+        });
+    }
+
+    /// Create a rotated or mirrored copy of the grid.
+    pub fn transformed(&self, transform: GridTransform) -> BitGrid {
+        let mut out = BitGrid::new(transform.apply_size(self.size()));
+        for (new, old) in transform.source_indices(self.size()).enumerate() {
+            out.set_index(new, self.get_index(old));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        "
+abc
+def
+"
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn transformed() {
+        let grid = grid();
+        let expected = [
+            (GridTransform::Identity, "abc\ndef\n"),
+            (GridTransform::Rotate90, "cf\nbe\nad\n"),
+            (GridTransform::Rotate180, "fed\ncba\n"),
+            (GridTransform::Rotate270, "da\neb\nfc\n"),
+            (GridTransform::FlipX, "cba\nfed\n"),
+            (GridTransform::FlipY, "def\nabc\n"),
+            (GridTransform::Transpose, "fc\neb\nda\n"),
+            (GridTransform::AntiTranspose, "ad\nbe\ncf\n"),
+        ];
+        for (t, s) in expected {
+            assert_eq!(s, grid.transformed(t).to_string(), "{t:?}");
+        }
+    }
+
+    #[test]
+    fn in_place() {
+        let numbers: Grid<u32> = Grid::from_parts((0..16).collect(), UVec2::splat(4));
+        let mut floats = FloatGrid::new(numbers.size());
+        numbers
+            .iter_xy()
+            .for_each(|(p, v)| floats.set_value(p, *v as f32));
+        let bits = {
+            let mut bits = BitGrid::new(numbers.size());
+            numbers.iter_xy().for_each(|(p, v)| bits.set(p, v % 3 == 1));
+            bits
+        };
+        for t in GridTransform::ALL {
+            let mut g = numbers.clone();
+            g.transform(t);
+            assert_eq!(numbers.transformed(t), g, "{t:?}");
+
+            let mut f = floats.clone();
+            f.transform(t);
+            assert_eq!(floats.transformed(t), f);
+            assert!(f.iter_xy().all(|(p, v)| g[p] as f32 == v));
+
+            let mut b = bits.clone();
+            b.transform(t);
+            assert!(b.iter_xy().all(|(p, v)| (g[p] % 3 == 1) == v));
+            assert!(bits.transformed(t).bits() == b.bits());
+        }
+
+        let mut grid = grid();
+        grid.transform(GridTransform::Rotate180);
+        assert_eq!("fed\ncba\n", grid.to_string());
+
+        // Transforms that keep the axes work in place on any size.
+        let numbers: Grid<u32> = Grid::from_parts((0..15).collect(), UVec2::new(5, 3));
+        let bits = {
+            let mut bits = BitGrid::new(numbers.size());
+            numbers.iter_xy().for_each(|(p, v)| bits.set(p, v % 3 == 1));
+            bits
+        };
+        for t in GridTransform::ALL.into_iter().filter(|t| !t.swaps_axes()) {
+            let mut g = numbers.clone();
+            g.transform(t);
+            assert_eq!(numbers.transformed(t), g, "{t:?}");
+
+            let mut b = bits.clone();
+            b.transform(t);
+            assert!(bits.transformed(t).bits() == b.bits(), "{t:?}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn in_place_non_square() {
+        grid().transform(GridTransform::Rotate90);
+    }
+
+    #[test]
+    fn compose() {
+        use GridTransform::*;
+        assert_eq!(Rotate180, Rotate90.then(Rotate90));
+        assert_eq!(Identity, Rotate90.then(Rotate270));
+        assert_eq!(Rotate180, FlipX.then(FlipY));
+        assert_eq!(Transpose, Rotate90.then(FlipX));
+        for t in GridTransform::ALL {
+            assert_eq!(Identity, t.then(t.inverse()));
+        }
+    }
+
+    #[test]
+    fn points_and_rects() {
+        let size = [5, 3];
+        let t = GridTransform::Rotate90;
+        assert_eq!(IVec2::new(-2, 1), t.apply([1, 2]));
+        assert_eq!(IVec2::new(2, 0), t.apply_in([0, 0], size));
+        assert_eq!(IVec2::new(0, 4), t.apply_in([4, 2], size));
+
+        let rect = GridRect::new([1, 0], [2, 3]);
+        assert_eq!(GridRect::new([0, 1], [3, 2]), t.apply_rect_in(rect, size));
+        assert_eq!(GridRect::new([-2, 1], [3, 2]), t.apply_rect(rect));
+
+        for t in GridTransform::ALL {
+            let rect = GridRect::new([1, 0], [2, 3]);
+            let tiles: Vec<_> = rect.iter_points().map(|p| t.apply_in(p, size)).collect();
+            let moved = t.apply_rect_in(rect, size);
+            assert_eq!(moved.size, t.apply_size(rect.size), "{t:?}");
+            assert!(tiles.iter().all(|p| moved.contains_point(*p)), "{t:?}");
+        }
+    }
+
+    #[test]
+    fn empty_rects() {
+        let size = [5, 3];
+        for t in GridTransform::ALL {
+            for rect in [
+                GridRect::new([0, 0], [0, 0]),
+                GridRect::new([1, 1], [0, 2]),
+                GridRect::new([1, 1], [2, 0]),
+            ] {
+                let expected = t.apply_size(rect.size);
+                assert_eq!(expected, t.apply_rect(rect).size, "{t:?}");
+                assert_eq!(expected, t.apply_rect_in(rect, size).size, "{t:?}");
+            }
+        }
+        let rect = GridTransform::Rotate90.apply_rect(GridRect::new([0, 0], [0, 2]));
+        assert_eq!(GridRect::new([-1, 0], [2, 0]), rect);
+    }
+}
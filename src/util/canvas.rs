@@ -1,59 +1,203 @@
-use crate::{GridPoint, GridShape, GridSize};
-use glam::UVec2;
+use std::fmt::{Display, Write};
 
+use crate::{BitGrid, Grid, GridPoint, GridShape, GridSize, Pivot, SizedGrid};
+use glam::{IVec2, UVec2};
+
+/// A grid of glyphs for debug rendering points, shapes and grids.
+///
+/// Points are in world space, where world origin is placed on the canvas at
+/// the position of the canvas' origin [Pivot]. Points outside the canvas are
+/// ignored.
+///
+/// # Example
+///
+/// ```
+/// use sark_grids::{geometry::GridRect, util::Canvas, Pivot};
+///
+/// let mut canvas = Canvas::new([4, 3]).with_origin(Pivot::BottomLeft);
+/// canvas.put_shape_labeled(GridRect::new([1, 0], [2, 2]), '█', "wall");
+/// canvas.put([0, 2], '@');
+///
+/// assert_eq!("@...\n.██.\n.██.\n█: wall\n", canvas.to_string());
+/// ```
+#[derive(Debug, Clone)]
 pub struct Canvas {
-    size: UVec2,
-    string: String,
+    grid: Grid<char>,
+    origin: Pivot,
+    background: char,
+    axes: bool,
+    legend: Vec<(char, String)>,
 }
 
 impl Canvas {
+    /// Create a new canvas with world origin at it's center.
     pub fn new(size: impl GridSize) -> Canvas {
-        let string = str::repeat(".", size.tile_count());
-
         Canvas {
-            size: size.to_uvec2(),
-            string,
+            grid: Grid::filled('.', size.to_uvec2()),
+            origin: Pivot::Center,
+            background: '.',
+            axes: false,
+            legend: Vec::new(),
         }
     }
 
-    pub fn put(&mut self, pos: impl GridPoint, glyph: char) {
-        let i = self.to_index(pos);
-        self.string
-            .replace_range(i..i + 1, std::str::from_utf8(&[glyph as u8]).unwrap());
+    /// Set the pivot of the canvas that world origin is placed at.
+    pub fn with_origin(mut self, origin: Pivot) -> Self {
+        self.origin = origin;
+        self
     }
 
-    pub fn put_bl(&mut self, pos: impl GridPoint, glyph: char) {
-        let i = self.to_index(pos.to_ivec2() - self.size.as_ivec2() / 2);
-        self.string
-            .replace_range(i..i + 1, std::str::from_utf8(&[glyph as u8]).unwrap());
+    /// Set the glyph for empty tiles. Tiles that are already drawn and the
+    /// legend are kept.
+    pub fn with_background(mut self, background: char) -> Self {
+        let old = std::mem::replace(&mut self.background, background);
+        self.grid
+            .iter_mut()
+            .filter(|g| **g == old)
+            .for_each(|g| *g = background);
+        self
     }
 
-    fn to_index(&self, point: impl GridPoint) -> usize {
-        let p = point.to_ivec2() + self.size.as_ivec2() / 2;
-        p.as_index(self.size)
+    /// Render world coordinate labels along the left and bottom of the canvas.
+    pub fn with_axes(mut self, axes: bool) -> Self {
+        self.axes = axes;
+        self
     }
 
-    pub fn print(&self) {
-        let chars: Vec<_> = self.string.chars().collect();
-        for line in chars.chunks(self.size.x as usize).rev() {
-            println!("{}", String::from_iter(line.iter()));
+    /// The size of the canvas, not including axis labels or legend.
+    pub fn size(&self) -> UVec2 {
+        self.grid.size()
+    }
+
+    /// Draw a glyph at the given world position.
+    pub fn put(&mut self, pos: impl GridPoint, glyph: char) {
+        let p = self.to_canvas(pos);
+        if let Some(tile) = self.grid.get_mut(p) {
+            *tile = glyph;
+        }
+    }
+
+    /// Draw a glyph at the given position relative to the bottom left of the
+    /// canvas, ignoring the canvas' origin.
+    pub fn put_bl(&mut self, pos: impl GridPoint, glyph: char) {
+        if let Some(tile) = self.grid.get_mut(pos) {
+            *tile = glyph;
         }
     }
 
+    /// Retrieve the glyph at the given world position.
+    ///
+    /// Returns [None] if the position is outside the canvas.
+    pub fn get(&self, pos: impl GridPoint) -> Option<char> {
+        self.grid.get(self.to_canvas(pos)).copied()
+    }
+
+    /// Draw every point of a shape with the given glyph.
+    ///
+    /// Shapes are drawn over anything already on the canvas, so multiple
+    /// shapes can be layered in the order they are drawn.
     pub fn put_shape(&mut self, shape: impl GridShape, glyph: char) {
         for p in shape.iter() {
             self.put(p, glyph);
         }
     }
 
+    /// Draw a shape with the given glyph and add it to the legend.
+    pub fn put_shape_labeled(&mut self, shape: impl GridShape, glyph: char, label: &str) {
+        self.put_shape(shape, glyph);
+        self.add_legend(glyph, label);
+    }
+
+    /// Add an entry to the legend, which is rendered below the canvas.
+    pub fn add_legend(&mut self, glyph: char, label: &str) {
+        self.legend.push((glyph, label.to_string()));
+    }
+
+    /// Draw a grid with it's bottom left tile at the given world position.
+    ///
+    /// Each grid value is converted to a glyph, values that return [None] are
+    /// not drawn.
+    pub fn put_grid<T>(
+        &mut self,
+        pos: impl GridPoint,
+        grid: &Grid<T>,
+        glyph: impl Fn(&T) -> Option<char>,
+    ) {
+        let pos = pos.to_ivec2();
+        for (p, v) in grid.iter_xy() {
+            if let Some(g) = glyph(v) {
+                self.put(pos + p, g);
+            }
+        }
+    }
+
+    /// Draw a [BitGrid] with it's bottom left tile at the given world
+    /// position.
+    ///
+    /// Each bit is converted to a glyph, bits that return [None] are not
+    /// drawn.
+    pub fn put_bit_grid(
+        &mut self,
+        pos: impl GridPoint,
+        grid: &BitGrid,
+        glyph: impl Fn(bool) -> Option<char>,
+    ) {
+        let pos = pos.to_ivec2();
+        for (p, v) in grid.iter_xy() {
+            if let Some(g) = glyph(v) {
+                self.put(pos + p, g);
+            }
+        }
+    }
+
+    /// Print the canvas to the console.
+    pub fn print(&self) {
+        print!("{}", self);
+    }
+
+    /// Clear all glyphs and the legend from the canvas.
     pub fn clear(&mut self) {
-        self.string = str::repeat(".", self.size.tile_count());
+        self.grid.iter_mut().for_each(|g| *g = self.background);
+        self.legend.clear();
+    }
+
+    fn to_canvas(&self, pos: impl GridPoint) -> IVec2 {
+        pos.to_ivec2() + self.origin.pivot_position(self.grid.size())
+    }
+}
+
+impl Display for Canvas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.axes {
+            f.write_str(&self.grid.to_string())?;
+        } else {
+            let offset = self.origin.pivot_position(self.grid.size());
+            let ys = (0..self.grid.height() as i32).map(|y| y - offset.y);
+            let label_width = ys.map(|y| y.to_string().len()).max().unwrap_or(0);
+            for y in (0..self.grid.height()).rev() {
+                let label = y as i32 - offset.y;
+                write!(f, "{:>w$} ", label, w = label_width)?;
+                self.grid.iter_row(y).try_for_each(|g| f.write_char(*g))?;
+                f.write_char('\n')?;
+            }
+            write!(f, "{:w$} ", "", w = label_width)?;
+            for x in 0..self.grid.width() as i32 {
+                let digit = (x - offset.x).unsigned_abs() % 10;
+                f.write_char(char::from_digit(digit, 10).unwrap())?;
+            }
+            f.write_char('\n')?;
+        }
+        for (glyph, label) in &self.legend {
+            writeln!(f, "{}: {}", glyph, label)?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Canvas;
+    use crate::{geometry::GridRect, BitGrid, Pivot};
 
     #[test]
     #[ignore]
@@ -81,4 +225,48 @@ mod tests {
 
         canvas.print();
     }
+
+    #[test]
+    fn centered() {
+        let mut canvas = Canvas::new([5, 3]);
+        canvas.put([0, 0], '@');
+        canvas.put([-2, 1], '★');
+        canvas.put([10, 10], '!');
+        canvas.put_bl([4, 0], 'x');
+
+        assert_eq!("★....\n..@..\n....x\n", canvas.to_string());
+        assert_eq!(Some('@'), canvas.get([0, 0]));
+        assert_eq!(None, canvas.get([3, 0]));
+    }
+
+    #[test]
+    fn layers() {
+        let mut canvas = Canvas::new([4, 4])
+            .with_origin(Pivot::BottomLeft)
+            .with_background(' ');
+        let mut bits = BitGrid::new([4, 1]);
+        bits.set_true([1, 0]);
+        canvas.put_bit_grid([0, 0], &bits, |b| b.then_some('#'));
+        canvas.put_shape_labeled(GridRect::new([2, 0], [2, 2]), 'r', "rect");
+
+        assert_eq!("    \n    \n  rr\n #rr\nr: rect\n", canvas.to_string());
+    }
+
+    #[test]
+    fn background_keeps_contents() {
+        let mut canvas = Canvas::new([3, 1]).with_origin(Pivot::BottomLeft);
+        canvas.put([0, 0], '@');
+        canvas.add_legend('@', "player");
+        let canvas = canvas.with_background(' ');
+
+        assert_eq!("@  \n@: player\n", canvas.to_string());
+    }
+
+    #[test]
+    fn axes() {
+        let mut canvas = Canvas::new([3, 3]).with_axes(true);
+        canvas.put([0, 0], '@');
+
+        assert_eq!(" 1 ...\n 0 .@.\n-1 ...\n   101\n", canvas.to_string());
+    }
 }
@@ -1,4 +1,4 @@
-//! Simple utility for printing an ascii grid to the console.
+//! Utilities for rendering grids, shapes and points to text for debugging.
 pub mod canvas;
 
 pub use canvas::Canvas;